@@ -0,0 +1,35 @@
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+use jonas_groth16::bn254::verify_groth16_proof as verify_bn254_groth16_proof;
+use jonas_groth16::verifier::{verify_groth16_batch, verify_groth16_proof, PreparedVerifyingKey};
+use types::{Groth16ParityCases, Groth16ParityOutput};
+pub fn main() {
+    let cases: Groth16ParityCases = borsh::from_slice(&sp1_zkvm::io::read_vec()).unwrap();
+
+    // unlike the recursive program, a rejected proof is a verdict rather than a panic
+    let mut verdicts = Vec::with_capacity(cases.proofs.len());
+    let mut batch_verdicts = Vec::with_capacity(cases.proofs.len());
+    for case in &cases.proofs {
+        let public_inputs = case.deserialize_public_inputs();
+        verdicts.push(verify_groth16_proof(&case.vk, &case.proof, &public_inputs).is_ok());
+        let batch_verdict = PreparedVerifyingKey::new(&case.vk).is_ok_and(|pvk| {
+            verify_groth16_batch(&pvk, &[(&case.proof, public_inputs.as_slice())]).is_ok()
+        });
+        batch_verdicts.push(batch_verdict);
+    }
+    let bn254_verdicts = cases
+        .bn254_proofs
+        .iter()
+        .map(|case| {
+            let public_inputs = case.deserialize_public_inputs();
+            verify_bn254_groth16_proof(&case.vk, &case.proof, &public_inputs).is_ok()
+        })
+        .collect();
+
+    let output = Groth16ParityOutput {
+        verdicts,
+        batch_verdicts,
+        bn254_verdicts,
+    };
+    sp1_zkvm::io::commit_slice(&borsh::to_vec(&output).unwrap());
+}
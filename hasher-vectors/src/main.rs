@@ -0,0 +1,17 @@
+#![no_main]
+
+use types::HasherVectorsOutput;
+use valence_coprocessor_core::{check_hasher_vectors, HASHER_VECTORS};
+sp1_zkvm::entrypoint!(main);
+pub fn main() {
+    // the vectors are compiled into the program, so the guest checks the same file as the host
+    let report = match check_hasher_vectors(HASHER_VECTORS) {
+        Ok(report) => report,
+        Err(e) => panic!("{e}"),
+    };
+    let output = HasherVectorsOutput {
+        checked: report.checked as u64,
+        skipped: report.skipped as u64,
+    };
+    sp1_zkvm::io::commit_slice(&borsh::to_vec(&output).unwrap());
+}
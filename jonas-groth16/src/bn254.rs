@@ -0,0 +1,168 @@
+use crate::verifier::{Groth16Error, Groth16Point};
+#[cfg(feature = "normal")]
+use normal_bn::{
+    arith::U256, pairing, pairing_batch, AffineG1, AffineG2, Fq, Fq2, Fr, Group, Gt, G1, G2,
+};
+use num_bigint::BigUint;
+#[cfg(all(feature = "sp1", not(feature = "normal")))]
+use sp1_bn_precompile::{
+    arith::U256, pairing, pairing_batch, AffineG1, AffineG2, Fq, Fq2, Fr, Group, Gt, G1, G2,
+};
+use types::{Bn254Groth16Proof, Bn254Groth16VerifyingKey};
+
+/// A BN254 verifying key, decoded once to verify any number of proofs.
+///
+/// This is the BN254 counterpart of [crate::verifier::PreparedVerifyingKey], with `e(alpha, beta)`
+/// computed up front. The key is trusted, so its points skip the curve and subgroup checks.
+pub struct PreparedVerifyingKey {
+    alpha_beta: Gt,
+    gamma: G2,
+    delta: G2,
+    ics: Vec<G1>,
+    hash: [u8; 32],
+}
+
+impl PreparedVerifyingKey {
+    /// Decodes and prepares the verifying key.
+    pub fn new(vk: &Bn254Groth16VerifyingKey) -> Result<Self, Groth16Error> {
+        let alpha = decode_vk_g1(&vk.alpha_g1, Groth16Point::Alpha)?;
+        let beta = decode_vk_g2(&vk.beta_g2, Groth16Point::Beta)?;
+
+        let ics = vk
+            .ic
+            .iter()
+            .enumerate()
+            .map(|(index, point)| decode_vk_g1(point, Groth16Point::Ic(index)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            alpha_beta: pairing(alpha, beta),
+            gamma: decode_vk_g2(&vk.gamma_g2, Groth16Point::Gamma)?,
+            delta: decode_vk_g2(&vk.delta_g2, Groth16Point::Delta)?,
+            ics,
+            hash: vk.hash(),
+        })
+    }
+
+    /// Returns the hash of the key, as computed by [Bn254Groth16VerifyingKey::hash].
+    pub fn hash(&self) -> [u8; 32] {
+        self.hash
+    }
+}
+
+/// Verifies a BN254 Groth16 proof against the verifying key and the public inputs.
+///
+/// This is the BN254 counterpart of [crate::verifier::verify_groth16_proof]; the key is prepared
+/// for this proof only.
+pub fn verify_groth16_proof(
+    vk: &Bn254Groth16VerifyingKey,
+    proof: &Bn254Groth16Proof,
+    public_inputs: &[BigUint],
+) -> Result<(), Groth16Error> {
+    verify_groth16_proof_prepared(&PreparedVerifyingKey::new(vk)?, proof, public_inputs)
+}
+
+/// Verifies a BN254 Groth16 proof against a prepared verifying key and the public inputs.
+///
+/// This is the BN254 counterpart of [crate::verifier::verify_groth16_proof_prepared], with the
+/// same pairing terms and the same error handling. The proof points are decoded with the on-curve
+/// and subgroup checks. In guest builds the curve arithmetic of the SP1 patched `substrate-bn` runs
+/// on the bn254 precompiles.
+pub fn verify_groth16_proof_prepared(
+    pvk: &PreparedVerifyingKey,
+    proof: &Bn254Groth16Proof,
+    public_inputs: &[BigUint],
+) -> Result<(), Groth16Error> {
+    if pvk.ics.len() != public_inputs.len() + 1 {
+        return Err(Groth16Error::WrongNumberOfPublicInputs {
+            expected: pvk.ics.len().saturating_sub(1),
+            found: public_inputs.len(),
+        });
+    }
+
+    let mut scalars = Vec::with_capacity(public_inputs.len());
+    for (index, input) in public_inputs.iter().enumerate() {
+        scalars.push(decode_fr(input).ok_or(Groth16Error::InvalidPublicInput { index })?);
+    }
+
+    let a = decode_g1(&proof.a, Groth16Point::A)?;
+    let b = decode_g2(&proof.b, Groth16Point::B)?;
+    let c = decode_g1(&proof.c, Groth16Point::C)?;
+
+    // vk_x = ic[0] + sum(ic[i] * public_inputs[i - 1])
+    let mut vk_x = pvk.ics[0];
+    for (ic, scalar) in pvk.ics.iter().skip(1).zip(scalars) {
+        vk_x = vk_x + *ic * scalar;
+    }
+
+    let terms = [(-a, b), (vk_x, pvk.gamma), (c, pvk.delta)];
+    if pairing_batch(&terms) * pvk.alpha_beta != Gt::one() {
+        return Err(Groth16Error::PairingFailed);
+    }
+
+    Ok(())
+}
+
+/// Decodes a public input, rejecting the values that aren't below the scalar field modulus.
+fn decode_fr(input: &BigUint) -> Option<Fr> {
+    let bytes = input.to_bytes_be();
+    if bytes.len() > 32 {
+        return None;
+    }
+    let mut buffer = [0u8; 32];
+    buffer[32 - bytes.len()..].copy_from_slice(&bytes);
+    Fr::new(U256::from_slice(&buffer).ok()?)
+}
+
+fn decode_coordinates(bytes: &[u8]) -> Option<(Fq, Fq)> {
+    let x = Fq::from_slice(&bytes[..32]).ok()?;
+    let y = Fq::from_slice(&bytes[32..]).ok()?;
+    Some((x, y))
+}
+
+fn decode_coordinates_g2(bytes: &[u8; 128]) -> Option<(Fq2, Fq2)> {
+    // the imaginary part comes first
+    let (x1, x0) = decode_coordinates(&bytes[..64])?;
+    let (y1, y0) = decode_coordinates(&bytes[64..])?;
+    Some((Fq2::new(x0, x1), Fq2::new(y0, y1)))
+}
+
+/// Decodes an untrusted G1 point, checking it's on the curve; G1 has no cofactor.
+fn decode_g1(bytes: &[u8; 64], point: Groth16Point) -> Result<G1, Groth16Error> {
+    decode_coordinates(bytes)
+        .and_then(|(x, y)| AffineG1::new(x, y).ok())
+        .map(G1::from)
+        .ok_or(Groth16Error::InvalidG1Encoding { point })
+}
+
+/// Decodes an untrusted G2 point, checking it's on the curve and in the prime-order subgroup.
+fn decode_g2(bytes: &[u8; 128], point: Groth16Point) -> Result<G2, Groth16Error> {
+    decode_coordinates_g2(bytes)
+        .and_then(|(x, y)| AffineG2::new(x, y).ok())
+        .map(G2::from)
+        .ok_or(Groth16Error::InvalidG2Encoding { point })
+}
+
+/// Decodes a G1 point of the trusted verifying key, skipping the on-curve check.
+///
+/// As in the Ethereum precompiles, the all-zero encoding is the point at infinity.
+fn decode_vk_g1(bytes: &[u8; 64], point: Groth16Point) -> Result<G1, Groth16Error> {
+    if bytes.iter().all(|b| *b == 0) {
+        return Ok(G1::zero());
+    }
+    decode_coordinates(bytes)
+        .map(|(x, y)| G1::new(x, y, Fq::one()))
+        .ok_or(Groth16Error::InvalidG1Encoding { point })
+}
+
+/// Decodes a G2 point of the trusted verifying key, skipping the on-curve and subgroup checks.
+///
+/// As in the Ethereum precompiles, the all-zero encoding is the point at infinity.
+fn decode_vk_g2(bytes: &[u8; 128], point: Groth16Point) -> Result<G2, Groth16Error> {
+    if bytes.iter().all(|b| *b == 0) {
+        return Ok(G2::zero());
+    }
+    decode_coordinates_g2(bytes)
+        .map(|(x, y)| G2::new(x, y, Fq2::one()))
+        .ok_or(Groth16Error::InvalidG2Encoding { point })
+}
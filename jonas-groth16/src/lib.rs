@@ -1,3 +1,4 @@
+pub mod bn254;
 pub mod verifier;
 pub const BN254_BASE_FIELD_MODULUS: &str =
     "21888242871839275222246405745257275088696311157297823662689037894645226208583";
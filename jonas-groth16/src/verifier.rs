@@ -1,120 +1,497 @@
-use crate::BLS12_381_BASE_FIELD_MODULUS as MODULUS;
-pub use ark_bls12_381::G1Affine as G1;
-use ark_bls12_381::{self, Fq, Fr};
-use ark_ec::{AffineRepr, CurveGroup};
-use ark_ff::{BigInteger, PrimeField};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 #[cfg(feature = "normal")]
-use normal_bls::{multi_miller_loop, G1Affine, G2Affine, G2Prepared, Gt};
+use normal_bls::{
+    multi_miller_loop, pairing, G1Affine, G1Projective, G2Affine, G2Prepared, Gt, Scalar,
+};
 use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
 #[cfg(all(feature = "sp1", not(feature = "normal")))]
-use sp1_bls_precompile::{multi_miller_loop, G1Affine, G2Affine, G2Prepared, Gt};
-use std::str::FromStr;
+use sp1_bls_precompile::{
+    multi_miller_loop, pairing, G1Affine, G1Projective, G2Affine, G2Prepared, Gt, Scalar,
+};
+#[cfg(all(feature = "sp1", not(feature = "normal"), target_os = "zkvm"))]
+use sp1_lib::{syscall_bls12381_add, syscall_bls12381_double};
+use std::fmt;
+use types::{Groth16Proof, Groth16VerifyingKey};
 
-#[allow(clippy::too_many_arguments)]
+/// Evaluates the expression inside an SP1 cycle tracker of the provided name, when running in the
+/// zkVM. The cycles of every evaluation add up in the execution report.
+macro_rules! track_cycles {
+    ($name:literal, $expression:expr) => {{
+        #[cfg(target_os = "zkvm")]
+        println!(concat!("cycle-tracker-report-start: ", $name));
+        let result = $expression;
+        #[cfg(target_os = "zkvm")]
+        println!(concat!("cycle-tracker-report-end: ", $name));
+        result
+    }};
+}
+
+/// A point of a Groth16 verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Groth16Point {
+    A,
+    B,
+    C,
+    Alpha,
+    Beta,
+    Gamma,
+    Delta,
+    /// The IC point at the provided index.
+    Ic(usize),
+}
+
+/// The reasons a Groth16 proof is rejected by [verify_groth16_proof] and
+/// [crate::bn254::verify_groth16_proof].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Groth16Error {
+    /// The G1 point isn't a valid compressed encoding, or a proof point isn't in the prime-order
+    /// subgroup.
+    InvalidG1Encoding { point: Groth16Point },
+    /// The G2 point isn't a valid compressed encoding, or a proof point isn't in the prime-order
+    /// subgroup.
+    InvalidG2Encoding { point: Groth16Point },
+    /// The public input at the provided index isn't a canonical scalar, i.e. it isn't below the
+    /// scalar field modulus.
+    InvalidPublicInput { index: usize },
+    /// The number of public inputs doesn't match the IC points of the verifying key.
+    WrongNumberOfPublicInputs { expected: usize, found: usize },
+    /// The pairing equation doesn't hold.
+    PairingFailed,
+}
+
+impl fmt::Display for Groth16Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidG1Encoding { point } => write!(f, "invalid G1 encoding of {point:?}"),
+            Self::InvalidG2Encoding { point } => write!(f, "invalid G2 encoding of {point:?}"),
+            Self::InvalidPublicInput { index } => {
+                write!(
+                    f,
+                    "public input {index} isn't below the scalar field modulus"
+                )
+            }
+            Self::WrongNumberOfPublicInputs { expected, found } => {
+                write!(f, "expected {expected} public inputs, got {found}")
+            }
+            Self::PairingFailed => write!(f, "the pairing check failed"),
+        }
+    }
+}
+
+impl std::error::Error for Groth16Error {}
+
+/// A BLS12-381 verifying key, decoded and prepared once to verify any number of proofs.
+///
+/// The G2 points are prepared for the Miller loop, and `e(alpha, beta)`, which doesn't depend on
+/// the proof, is computed up front. The key is trusted once its hash is committed, so its points
+/// are decoded without the subgroup checks.
+pub struct PreparedVerifyingKey {
+    alpha: G1Affine,
+    beta: G2Prepared,
+    alpha_beta: Gt,
+    gamma: G2Prepared,
+    delta: G2Prepared,
+    ics: Vec<G1Affine>,
+    hash: [u8; 32],
+}
+
+impl PreparedVerifyingKey {
+    /// Decodes and prepares the verifying key.
+    pub fn new(vk: &Groth16VerifyingKey) -> Result<Self, Groth16Error> {
+        let alpha = decode_vk_g1(&vk.alpha_g1, Groth16Point::Alpha)?;
+        let beta = decode_vk_g2(&vk.beta_g2, Groth16Point::Beta)?;
+        let gamma = decode_vk_g2(&vk.gamma_g2, Groth16Point::Gamma)?;
+        let delta = decode_vk_g2(&vk.delta_g2, Groth16Point::Delta)?;
+
+        let ics = vk
+            .ic
+            .iter()
+            .enumerate()
+            .map(|(index, point)| decode_vk_g1(point, Groth16Point::Ic(index)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            alpha,
+            beta: G2Prepared::from(beta),
+            alpha_beta: pairing(&alpha, &beta),
+            gamma: G2Prepared::from(gamma),
+            delta: G2Prepared::from(delta),
+            ics,
+            hash: vk.hash(),
+        })
+    }
+
+    /// Returns the hash of the key, as computed by [Groth16VerifyingKey::hash].
+    pub fn hash(&self) -> [u8; 32] {
+        self.hash
+    }
+}
+
+/// Verifies a BLS12-381 Groth16 proof against the verifying key and the public inputs.
+///
+/// The key is prepared for this proof only; [verify_groth16_proof_prepared] verifies several proofs
+/// of the same key without preparing it again.
+///
+/// Malformed inputs are rejected with a [Groth16Error] instead of aborting, so the caller decides
+/// how a failure is handled.
 pub fn verify_groth16_proof(
-    g1_affine_points_serialized: Vec<[u8; 48]>,
-    g2_affine_points_serialized: Vec<[u8; 96]>,
-    public_inputs: Vec<BigUint>,
-    ics_serialized: Vec<Vec<u8>>,
-) -> bool {
-    let mut g1_affine_points: Vec<G1Affine> = vec![];
-    let mut g2_affine_points: Vec<G2Prepared> = vec![];
-
-    for point in g1_affine_points_serialized {
-        g1_affine_points.push(G1Affine::from_compressed_unchecked(&point).unwrap());
+    vk: &Groth16VerifyingKey,
+    proof: &Groth16Proof,
+    public_inputs: &[BigUint],
+) -> Result<(), Groth16Error> {
+    verify_groth16_proof_prepared(&PreparedVerifyingKey::new(vk)?, proof, public_inputs)
+}
+
+/// Verifies a BLS12-381 Groth16 proof against a prepared verifying key and the public inputs.
+///
+/// The pairing terms are `(-A, B)`, `(vk_x, gamma)` and `(C, delta)`, with vk_x derived from the IC
+/// points of the key and the public inputs, and the precomputed `e(alpha, beta)` is added after the
+/// final exponentiation. Callers should bind the key to the output, e.g. with
+/// [PreparedVerifyingKey::hash].
+///
+/// The proof points are untrusted: they're decoded with the on-curve and subgroup checks, which the
+/// SP1 patched `bls12_381` runs on top of the BLS12-381 precompiles. Public inputs must be
+/// canonical scalars, so two inputs can't alias the same field element.
+pub fn verify_groth16_proof_prepared(
+    pvk: &PreparedVerifyingKey,
+    proof: &Groth16Proof,
+    public_inputs: &[BigUint],
+) -> Result<(), Groth16Error> {
+    let inputs = decode_public_inputs(pvk, public_inputs)?;
+
+    let a = decode_g1(&proof.a, Groth16Point::A)?;
+    let b = decode_g2(&proof.b, Groth16Point::B)?;
+    let c = decode_g1(&proof.c, Groth16Point::C)?;
+
+    // vk_x = ics[0] + sum(ics[i] * public_inputs[i - 1])
+    let scalars: Vec<Scalar> = std::iter::once(Scalar::from(1u64)).chain(inputs).collect();
+    let vk_x = G1Affine::from(msm(&pvk.ics, &scalars));
+
+    let neg_a = -a;
+    let b = G2Prepared::from(b);
+    let terms = [(&neg_a, &b), (&vk_x, &pvk.gamma), (&c, &pvk.delta)];
+
+    check_pairing(&terms, pvk.alpha_beta)
+}
+
+/// Verifies a batch of BLS12-381 Groth16 proofs of the same verifying key, each with its public
+/// inputs.
+///
+/// The pairing equations of the proofs are folded into a single one with a random linear
+/// combination, so the batch costs one multi-Miller loop and a single final exponentiation instead
+/// of one per proof. The coefficients are derived by Fiat-Shamir from the key, the proofs and the
+/// public inputs, so they can't be known before the batch is fixed. A batch is accepted only if
+/// every proof passes [verify_groth16_proof_prepared], except with a probability of about
+/// `2^-128`.
+///
+/// The points and the inputs are checked as in [verify_groth16_proof_prepared]. A rejected batch
+/// doesn't tell which proof is invalid; [verify_groth16_proof_prepared] locates it.
+pub fn verify_groth16_batch(
+    pvk: &PreparedVerifyingKey,
+    batch: &[(&Groth16Proof, &[BigUint])],
+) -> Result<(), Groth16Error> {
+    let inputs = batch
+        .iter()
+        .map(|(_, public_inputs)| decode_public_inputs(pvk, public_inputs))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // e(-r * A, B) for every proof, followed by
+    // e(sum(r) * alpha, beta) * e(sum(r * vk_x), gamma) * e(sum(r * C), delta)
+    let mut proof_terms = Vec::with_capacity(batch.len());
+    let mut r_sum = Scalar::from(0u64);
+    let mut c_sum = G1Projective::identity();
+    let mut scalars = vec![Scalar::from(0u64); pvk.ics.len()];
+
+    for (((proof, _), inputs), r) in batch.iter().zip(inputs).zip(batch_coefficients(pvk, batch)) {
+        let a = decode_g1(&proof.a, Groth16Point::A)?;
+        let b = decode_g2(&proof.b, Groth16Point::B)?;
+        let c = decode_g1(&proof.c, Groth16Point::C)?;
+
+        let mut r_bytes = [0u8; 32];
+        r_bytes[..16].copy_from_slice(&r);
+        let r_scalar: Scalar =
+            Option::from(Scalar::from_bytes(&r_bytes)).expect("a 128 bits scalar is canonical");
+
+        let a: G1Projective = -a * r_scalar;
+        proof_terms.push((G1Affine::from(a), G2Prepared::from(b)));
+        r_sum += r_scalar;
+        c_sum += c * r_scalar;
+
+        // sum(r * vk_x) is accumulated as the scalars of a single combination of the IC points
+        scalars[0] += r_scalar;
+        for (scalar, input) in scalars[1..].iter_mut().zip(inputs) {
+            *scalar += r_scalar * input;
+        }
+    }
+
+    // the Miller loop term of alpha is cheaper than raising e(alpha, beta) to sum(r)
+    let alpha = G1Affine::from(pvk.alpha * r_sum);
+    let vk_x = G1Affine::from(msm(&pvk.ics, &scalars));
+    let c_sum = G1Affine::from(c_sum);
+
+    let mut terms: Vec<_> = proof_terms.iter().map(|(g1, g2)| (g1, g2)).collect();
+    terms.push((&alpha, &pvk.beta));
+    terms.push((&vk_x, &pvk.gamma));
+    terms.push((&c_sum, &pvk.delta));
+
+    check_pairing(&terms, Gt::identity())
+}
+
+/// Derives the 128 bits coefficients of the random linear combination of a batch.
+///
+/// The seed is the SHA-256 hash of the key hash, then every proof followed by its public inputs,
+/// as 32 bytes big-endian scalars. The coefficient of the proof at index `i` is the first half of
+/// the SHA-256 hash of the seed and `i`, as 8 bytes little-endian.
+fn batch_coefficients(
+    pvk: &PreparedVerifyingKey,
+    batch: &[(&Groth16Proof, &[BigUint])],
+) -> impl Iterator<Item = [u8; 16]> {
+    let mut hasher = Sha256::new();
+    hasher.update(pvk.hash);
+    for (proof, public_inputs) in batch {
+        hasher.update(proof.a);
+        hasher.update(proof.b);
+        hasher.update(proof.c);
+        for input in public_inputs.iter() {
+            // the inputs are canonical, so they fit in 32 bytes
+            let bytes = input.to_bytes_be();
+            hasher.update([0u8; 32][bytes.len()..].iter());
+            hasher.update(&bytes);
+        }
     }
+    let seed: [u8; 32] = hasher.finalize().into();
 
-    for point in g2_affine_points_serialized {
-        g2_affine_points.push(G2Prepared::from(
-            G2Affine::from_compressed_unchecked(&point).unwrap(),
-        ));
+    (0..batch.len() as u64).map(move |i| {
+        let digest = Sha256::new()
+            .chain_update(seed)
+            .chain_update(i.to_le_bytes())
+            .finalize();
+        let mut r = [0u8; 16];
+        r.copy_from_slice(&digest[..16]);
+        r
+    })
+}
+
+/// Checks the number of public inputs against the key, and decodes them as scalars.
+///
+/// Every input must be canonical, i.e. below the scalar field modulus.
+fn decode_public_inputs(
+    pvk: &PreparedVerifyingKey,
+    public_inputs: &[BigUint],
+) -> Result<Vec<Scalar>, Groth16Error> {
+    if pvk.ics.len() != public_inputs.len() + 1 {
+        return Err(Groth16Error::WrongNumberOfPublicInputs {
+            expected: pvk.ics.len().saturating_sub(1),
+            found: public_inputs.len(),
+        });
     }
 
-    let mut ics: Vec<G1> = vec![];
-    for point in ics_serialized {
-        ics.push(G1::deserialize_compressed_unchecked::<&[u8]>(&point).unwrap());
+    let mut scalars = Vec::with_capacity(public_inputs.len());
+    for (index, input) in public_inputs.iter().enumerate() {
+        let bytes = input.to_bytes_le();
+        let scalar = (bytes.len() <= 32)
+            .then(|| {
+                let mut buffer = [0u8; 32];
+                buffer[..bytes.len()].copy_from_slice(&bytes);
+                Option::<Scalar>::from(Scalar::from_bytes(&buffer))
+            })
+            .flatten()
+            .ok_or(Groth16Error::InvalidPublicInput { index })?;
+        scalars.push(scalar);
     }
-    let mut vk_x: G1 = ics[0];
-    for (idx, ic) in ics.into_iter().enumerate().skip(1) {
-        let ic_coords = extract_g1_coordinates(ic);
-        let ic_scalar: G1 = scalar_mul(ic_coords.0, ic_coords.1, public_inputs[idx - 1].clone());
-        let ic_scalar_coords = extract_g1_coordinates(ic_scalar);
-        let vk_x_as_coordinates = extract_g1_coordinates(vk_x);
-        let vk_x_as_coords = vk_x_as_coordinates.clone();
-        vk_x = add_g1_as_coordinates(
-            vk_x_as_coords.0,
-            vk_x_as_coords.1,
-            ic_scalar_coords.0,
-            ic_scalar_coords.1,
-        );
+
+    Ok(scalars)
+}
+
+/// Computes `sum(points[i] * scalars[i])` as a multi-scalar multiplication.
+///
+/// The points share a single chain of doublings, followed by an addition for every set bit of
+/// their scalars.
+#[cfg(not(all(feature = "sp1", not(feature = "normal"), target_os = "zkvm")))]
+fn msm(points: &[G1Affine], scalars: &[Scalar]) -> G1Projective {
+    let scalars: Vec<[u8; 32]> = scalars.iter().map(Scalar::to_bytes).collect();
+
+    let mut sum = G1Projective::identity();
+    for bit in (0..256).rev() {
+        sum = sum.double();
+        for (point, scalar) in points.iter().zip(&scalars) {
+            if (scalar[bit / 8] >> (bit % 8)) & 1 == 1 {
+                sum += point;
+            }
+        }
     }
-    let mut vk_x_buffer = vec![];
-    vk_x.serialize_compressed(&mut vk_x_buffer).unwrap();
-    assert_eq!(
-        G1Affine::from_compressed_unchecked(&vk_x_buffer.try_into().unwrap()).unwrap(),
-        *g1_affine_points.get(2).unwrap()
-    );
-
-    let pairing_inputs: Vec<_> = g1_affine_points
-        .iter()
-        .zip(g2_affine_points.iter())
-        .collect();
 
-    let miller_result = multi_miller_loop(&pairing_inputs);
-    miller_result.final_exponentiation() == Gt::identity()
+    sum
+}
+
+/// Computes `sum(points[i] * scalars[i])` as a multi-scalar multiplication.
+///
+/// Runs the chain of doublings and additions of the native MSM on affine points, through the
+/// SP1 BLS12-381 add and double precompiles. The add precompile can't take a point and itself or
+/// its negation, which share their x coordinate; these are doubled or cancelled out instead.
+#[cfg(all(feature = "sp1", not(feature = "normal"), target_os = "zkvm"))]
+fn msm(points: &[G1Affine], scalars: &[Scalar]) -> G1Projective {
+    let scalars: Vec<[u8; 32]> = scalars.iter().map(Scalar::to_bytes).collect();
+    let points: Vec<Option<[u32; 24]>> = points.iter().map(g1_to_limbs).collect();
+
+    // `None` is the identity
+    let mut sum: Option<[u32; 24]> = None;
+    track_cycles!("msm", {
+        for bit in (0..256).rev() {
+            if let Some(sum) = sum.as_mut() {
+                unsafe { syscall_bls12381_double(sum) };
+            }
+            for (point, scalar) in points.iter().zip(&scalars) {
+                let Some(point) = point else {
+                    continue;
+                };
+                if (scalar[bit / 8] >> (bit % 8)) & 1 == 0 {
+                    continue;
+                }
+                sum = match sum {
+                    None => Some(*point),
+                    Some(mut sum) if sum[..12] == point[..12] => (sum == *point).then(|| {
+                        unsafe { syscall_bls12381_double(&mut sum) };
+                        sum
+                    }),
+                    Some(mut sum) => {
+                        unsafe { syscall_bls12381_add(&mut sum, point) };
+                        Some(sum)
+                    }
+                };
+            }
+        }
+    });
+
+    sum.map(|sum| G1Projective::from(g1_from_limbs(&sum)))
+        .unwrap_or_else(G1Projective::identity)
+}
+
+/// Splits the affine coordinates `x || y` of a point into the little-endian 32-bit limbs the SP1
+/// precompiles operate on, or returns `None` for the identity.
+#[cfg(all(feature = "sp1", not(feature = "normal"), target_os = "zkvm"))]
+fn g1_to_limbs(point: &G1Affine) -> Option<[u32; 24]> {
+    if bool::from(point.is_identity()) {
+        return None;
+    }
 
-    // todo: constrain vk_x => this is important to verify the public inputs
-    // might have to fork the bls precompile for that, work in progress.
+    let mut bytes = point.to_uncompressed();
+    let mut limbs = [0u32; 24];
+    for (coordinate, limbs) in bytes.chunks_exact_mut(48).zip(limbs.chunks_exact_mut(12)) {
+        coordinate.reverse();
+        for (limb, word) in limbs.iter_mut().zip(coordinate.chunks_exact(4)) {
+            *limb = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+        }
+    }
+
+    Some(limbs)
 }
 
-pub fn add_g1_as_coordinates(p_x: BigUint, p_y: BigUint, q_x: BigUint, q_y: BigUint) -> G1 {
-    let p = G1::new_unchecked(
-        parse_biguint_to_fq(&p_x.to_string()),
-        parse_biguint_to_fq(&p_y.to_string()),
-    );
-    let q = G1::new_unchecked(
-        parse_biguint_to_fq(&q_x.to_string()),
-        parse_biguint_to_fq(&q_y.to_string()),
-    );
-    (p + q).into_affine()
+/// Joins the limbs of a point produced by the SP1 precompiles back into an affine point.
+#[cfg(all(feature = "sp1", not(feature = "normal"), target_os = "zkvm"))]
+fn g1_from_limbs(limbs: &[u32; 24]) -> G1Affine {
+    let mut bytes = [0u8; 96];
+    for (coordinate, limbs) in bytes.chunks_exact_mut(48).zip(limbs.chunks_exact(12)) {
+        for (word, limb) in coordinate.chunks_exact_mut(4).zip(limbs) {
+            word.copy_from_slice(&limb.to_le_bytes());
+        }
+        coordinate.reverse();
+    }
+
+    G1Affine::from_uncompressed_unchecked(&bytes).unwrap()
 }
 
-pub fn extract_g1_coordinates(p: G1) -> (BigUint, BigUint) {
-    (
-        fq_to_biguint(&p.x().unwrap()),
-        fq_to_biguint(&p.y().unwrap()),
-    )
+/// Checks that the product of the pairings of the terms and `extra` is the identity.
+fn check_pairing(terms: &[(&G1Affine, &G2Prepared)], extra: Gt) -> Result<(), Groth16Error> {
+    let miller_result = multi_miller_loop(terms);
+    if miller_result.final_exponentiation() + extra != Gt::identity() {
+        return Err(Groth16Error::PairingFailed);
+    }
+
+    Ok(())
 }
 
-pub fn scalar_mul(p_x: BigUint, p_y: BigUint, k: BigUint) -> G1 {
-    let p = G1::new_unchecked(
-        parse_biguint_to_fq(&p_x.to_string()),
-        parse_biguint_to_fq(&p_y.to_string()),
-    );
-    let scalar = Fr::from_be_bytes_mod_order(&k.to_bytes_be());
-    (p.into_group() * scalar).into_affine()
+/// Decodes an untrusted G1 point, checking it's on the curve and in the prime-order subgroup.
+fn decode_g1(bytes: &[u8; 48], point: Groth16Point) -> Result<G1Affine, Groth16Error> {
+    track_cycles!("decode_g1", Option::from(G1Affine::from_compressed(bytes)))
+        .ok_or(Groth16Error::InvalidG1Encoding { point })
 }
 
-pub fn parse_biguint_to_fq(value: &str) -> Fq {
-    let big_int = BigUint::parse_bytes(value.as_bytes(), 10).unwrap();
-    Fq::from(big_int)
+/// Decodes an untrusted G2 point, checking it's on the curve and in the prime-order subgroup.
+fn decode_g2(bytes: &[u8; 96], point: Groth16Point) -> Result<G2Affine, Groth16Error> {
+    track_cycles!("decode_g2", Option::from(G2Affine::from_compressed(bytes)))
+        .ok_or(Groth16Error::InvalidG2Encoding { point })
 }
 
-fn fq_to_biguint(x_fq: &Fq) -> BigUint {
-    let bigint_repr = x_fq.into_bigint();
-    BigUint::from_bytes_le(&bigint_repr.to_bytes_le())
+/// Decodes a G1 point of the trusted verifying key, skipping the subgroup check.
+fn decode_vk_g1(bytes: &[u8; 48], point: Groth16Point) -> Result<G1Affine, Groth16Error> {
+    track_cycles!(
+        "decode_vk_g1",
+        Option::from(G1Affine::from_compressed_unchecked(bytes))
+    )
+    .ok_or(Groth16Error::InvalidG1Encoding { point })
+}
+
+/// Decodes a G2 point of the trusted verifying key, skipping the subgroup check.
+fn decode_vk_g2(bytes: &[u8; 96], point: Groth16Point) -> Result<G2Affine, Groth16Error> {
+    track_cycles!(
+        "decode_vk_g2",
+        Option::from(G2Affine::from_compressed_unchecked(bytes))
+    )
+    .ok_or(Groth16Error::InvalidG2Encoding { point })
 }
 
-pub fn negate_g1_affine(p: G1) -> G1 {
-    let x_fq = p.x().unwrap();
-    let y_coord: BigUint = fq_to_biguint(&p.y().unwrap());
-    let base_field_modulus_biguint = BigUint::from_str(MODULUS).unwrap();
-    if y_coord == BigUint::ZERO && fq_to_biguint(&x_fq) == BigUint::ZERO {
-        G1::new_unchecked(Fq::from(0), Fq::from(0))
-    } else {
-        let neg_y_coord =
-            (base_field_modulus_biguint.clone() - y_coord) % base_field_modulus_biguint;
-        G1::new_unchecked(x_fq, Fq::from(neg_y_coord))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Fr, G1Affine as G1, G1Projective as ArkG1Projective};
+    use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_serialize::CanonicalSerialize;
+
+    #[test]
+    fn msm_matches_arkworks() {
+        let mut points: Vec<G1> = (1..=4u64)
+            .map(|i| (G1::generator() * Fr::from(7 * i + 3)).into_affine())
+            .collect();
+        // a repeated point and its negation, which the sum reaches along the way
+        points.push(points[1]);
+        points.push(-points[1]);
+        let scalars = [
+            Fr::from(0u64),
+            Fr::from(1u64),
+            Fr::from(u64::MAX) * Fr::from(u64::MAX),
+            -Fr::from(1u64),
+            Fr::from(1u64),
+            Fr::from(2u64),
+        ];
+
+        let decoded_points: Vec<G1Affine> = points
+            .iter()
+            .map(|point| {
+                let mut buffer = [0u8; 48];
+                point.serialize_compressed(&mut buffer[..]).unwrap();
+                decode_vk_g1(&buffer, Groth16Point::Alpha).unwrap()
+            })
+            .collect();
+        let decoded_scalars: Vec<Scalar> = scalars
+            .iter()
+            .map(|scalar| {
+                let mut buffer = [0u8; 32];
+                buffer.copy_from_slice(&scalar.into_bigint().to_bytes_le());
+                Option::from(Scalar::from_bytes(&buffer)).unwrap()
+            })
+            .collect();
+
+        for len in 0..=points.len() {
+            let expected = ArkG1Projective::msm(&points[..len], &scalars[..len])
+                .unwrap()
+                .into_affine();
+            let sum = G1Affine::from(msm(&decoded_points[..len], &decoded_scalars[..len]));
+
+            let mut buffer = [0u8; 48];
+            expected.serialize_compressed(&mut buffer[..]).unwrap();
+            assert_eq!(sum.to_compressed(), buffer);
+        }
     }
 }
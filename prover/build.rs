@@ -4,6 +4,10 @@ fn main() {
     build_program_with_args("../provable-program", Default::default());
     build_program_with_args("../recursive-program", Default::default());
     build_program_with_args("../recursive-arkworks-program", Default::default());
+    build_program_with_args("../recursive-compressed-program", Default::default());
     build_program_with_args("../simple-merkle-proofs", Default::default());
     build_program_with_args("../smt-opening-proofs", Default::default());
+    build_program_with_args("../smt-update-proofs", Default::default());
+    build_program_with_args("../hasher-vectors", Default::default());
+    build_program_with_args("../groth16-parity", Default::default());
 }
@@ -4,9 +4,18 @@ use ark_crypto_primitives::{
     sponge::poseidon::PoseidonConfig,
 };
 
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
 use ark_ed_on_bls12_381::Fq as FqBLS12_381;
+use ark_ff::PrimeField;
+use ark_groth16::{r1cs_to_qap::LibsnarkReduction, Groth16, Proof, VerifyingKey};
 use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar};
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_snark::SNARK;
+use ark_std::{
+    rand::{CryptoRng, Rng},
+    UniformRand,
+};
 
 pub struct PoseidonDemoCircuitBls12_381 {
     pub input: Option<FqBLS12_381>, // private input
@@ -51,29 +60,788 @@ impl ConstraintSynthesizer<FqBLS12_381> for PoseidonDemoCircuitBls12_381 {
     }
 }
 
+/// Proves the knowledge of a square root of the public input.
+pub struct SquareDemoCircuit<F: PrimeField> {
+    pub root: Option<F>,   // private input
+    pub square: Option<F>, // public input
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for SquareDemoCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let root_var = FpVar::new_witness(cs.clone(), || {
+            self.root.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let square_var =
+            FpVar::new_input(cs, || self.square.ok_or(SynthesisError::AssignmentMissing))?;
+
+        (&root_var * &root_var).enforce_equal(&square_var)?;
+
+        Ok(())
+    }
+}
+
+/// A circuit of random constraints, for the differential tests of the verifier.
+///
+/// Every public input is a random linear combination of the products of consecutive witnesses.
+#[derive(Clone)]
+pub struct RandomDemoCircuit<F: PrimeField> {
+    pub witnesses: Vec<F>,         // private inputs
+    pub coefficients: Vec<Vec<F>>, // one row per public input
+}
+
+impl<F: PrimeField> RandomDemoCircuit<F> {
+    pub fn rand<R: Rng>(rng: &mut R, input_count: usize, witness_count: usize) -> Self {
+        Self {
+            witnesses: (0..witness_count).map(|_| F::rand(rng)).collect(),
+            coefficients: (0..input_count)
+                .map(|_| (1..witness_count).map(|_| F::rand(rng)).collect())
+                .collect(),
+        }
+    }
+
+    pub fn public_inputs(&self) -> Vec<F> {
+        self.coefficients
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(self.witnesses.windows(2))
+                    .map(|(c, w)| *c * w[0] * w[1])
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for RandomDemoCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let public_inputs = self.public_inputs();
+        let witness_vars = self
+            .witnesses
+            .iter()
+            .map(|w| FpVar::new_witness(cs.clone(), || Ok(*w)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let products: Vec<_> = witness_vars.windows(2).map(|w| &w[0] * &w[1]).collect();
+
+        for (row, input) in self.coefficients.iter().zip(public_inputs) {
+            let input_var = FpVar::new_input(cs.clone(), || Ok(input))?;
+            let mut sum = FpVar::Constant(F::ZERO);
+            for (c, product) in row.iter().zip(&products) {
+                sum += product * *c;
+            }
+            sum.enforce_equal(&input_var)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A Groth16 proof with the verdict of `ark_groth16`, for the differential tests of the verifier.
+pub struct ParityCase<E: Pairing> {
+    pub description: &'static str,
+    pub vk: VerifyingKey<E>,
+    pub proof: Proof<E>,
+    pub public_inputs: Vec<E::ScalarField>,
+    pub accepted: bool,
+}
+
+/// Proves `circuit_count` random circuits, following every valid proof with mutations of the
+/// proof, the public inputs and the verifying key.
+///
+/// Only the cases described as valid are accepted by `ark_groth16`; the mutations must be
+/// rejected.
+pub fn parity_cases<E: Pairing, R: Rng + CryptoRng>(
+    rng: &mut R,
+    circuit_count: usize,
+) -> Vec<ParityCase<E>> {
+    let mut cases = Vec::new();
+    for _ in 0..circuit_count {
+        let input_count = rng.gen_range(1..=4);
+        let witness_count = rng.gen_range(2..=6);
+        let circuit = RandomDemoCircuit::<E::ScalarField>::rand(rng, input_count, witness_count);
+        let public_inputs = circuit.public_inputs();
+        let (pk, vk) =
+            Groth16::<E, LibsnarkReduction>::circuit_specific_setup(circuit.clone(), rng).unwrap();
+        let proof = Groth16::<E, LibsnarkReduction>::prove(&pk, circuit.clone(), rng).unwrap();
+
+        let mut push =
+            |description, vk: VerifyingKey<E>, proof: Proof<E>, public_inputs: Vec<_>| {
+                let accepted = Groth16::<E, LibsnarkReduction>::verify(&vk, &public_inputs, &proof)
+                    .unwrap_or(false);
+                cases.push(ParityCase {
+                    description,
+                    vk,
+                    proof,
+                    public_inputs,
+                    accepted,
+                });
+            };
+
+        push("valid", vk.clone(), proof.clone(), public_inputs.clone());
+
+        // the last IC point folded into the first one, so the proof holds with the point at infinity
+        let mut zero_ic = vk.clone();
+        let last = zero_ic.gamma_abc_g1.len() - 1;
+        zero_ic.gamma_abc_g1[0] = (zero_ic.gamma_abc_g1[0].into_group()
+            + zero_ic.gamma_abc_g1[last] * public_inputs[last - 1])
+            .into_affine();
+        zero_ic.gamma_abc_g1[last] = E::G1Affine::zero();
+        push(
+            "valid with an IC point at infinity",
+            zero_ic,
+            proof.clone(),
+            public_inputs.clone(),
+        );
+
+        let mut wrong_inputs = public_inputs.clone();
+        wrong_inputs[0] += E::ScalarField::from(1u32);
+        push(
+            "wrong public input",
+            vk.clone(),
+            proof.clone(),
+            wrong_inputs,
+        );
+
+        let mut missing_inputs = public_inputs.clone();
+        missing_inputs.pop();
+        push(
+            "missing public input",
+            vk.clone(),
+            proof.clone(),
+            missing_inputs,
+        );
+
+        let mutations = [
+            (
+                "negated A",
+                Proof {
+                    a: (-proof.a.into_group()).into_affine(),
+                    ..proof.clone()
+                },
+            ),
+            (
+                "negated B",
+                Proof {
+                    b: (-proof.b.into_group()).into_affine(),
+                    ..proof.clone()
+                },
+            ),
+            (
+                "swapped A and C",
+                Proof {
+                    a: proof.c,
+                    c: proof.a,
+                    ..proof.clone()
+                },
+            ),
+            (
+                "random C",
+                Proof {
+                    c: E::G1::rand(rng).into_affine(),
+                    ..proof.clone()
+                },
+            ),
+        ];
+        for (description, mutated) in mutations {
+            push(description, vk.clone(), mutated, public_inputs.clone());
+        }
+
+        let swapped = VerifyingKey {
+            gamma_g2: vk.delta_g2,
+            delta_g2: vk.gamma_g2,
+            ..vk.clone()
+        };
+        push(
+            "swapped gamma and delta",
+            swapped,
+            proof.clone(),
+            public_inputs.clone(),
+        );
+
+        let mut swapped = vk.clone();
+        swapped.gamma_abc_g1.swap(0, 1);
+        push(
+            "swapped IC points",
+            swapped,
+            proof.clone(),
+            public_inputs.clone(),
+        );
+
+        // the same circuit with a fresh setup
+        let (_, other_vk) =
+            Groth16::<E, LibsnarkReduction>::circuit_specific_setup(circuit, rng).unwrap();
+        push("key of another setup", other_vk, proof, public_inputs);
+    }
+    cases
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
     use std::path::Path;
+    use std::time::Instant;
 
     use super::*;
+    use ark_bls12_381::{g1, g2, Bls12_381, Fr};
+    use ark_bn254::{Bn254, Fr as FrBn254};
     use ark_crypto_primitives::crh::{poseidon::CRH, CRHScheme};
-    use ark_ec::scalar_mul;
+    use ark_ec::pairing::Pairing;
+    use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+    use ark_ec::{AffineRepr, CurveGroup};
     use ark_ed_on_bls12_381::Fq as FqBLS12_381;
+    use ark_ff::{BigInteger, Field, PrimeField};
     use ark_groth16::{r1cs_to_qap::LibsnarkReduction, Groth16};
-    use ark_serialize::CanonicalSerialize;
+    use ark_groth16::{Proof, VerifyingKey};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
     use ark_snark::SNARK;
     use ark_std::{rand::rngs::StdRng, rand::SeedableRng};
-    use jonas_groth16::verifier::add_g1_as_coordinates;
-    use jonas_groth16::verifier::extract_g1_coordinates;
-    use jonas_groth16::verifier::negate_g1_affine;
-    use jonas_groth16::verifier::scalar_mul;
-    use jonas_groth16::verifier::verify_groth16_proof;
+    use jonas_groth16::bn254::{
+        verify_groth16_proof as verify_bn254_groth16_proof,
+        verify_groth16_proof_prepared as verify_bn254_groth16_proof_prepared,
+        PreparedVerifyingKey as Bn254PreparedVerifyingKey,
+    };
+    use jonas_groth16::verifier::{
+        verify_groth16_batch, verify_groth16_proof, verify_groth16_proof_prepared,
+        PreparedVerifyingKey,
+    };
+    use jonas_groth16::verifier::{Groth16Error, Groth16Point};
+    use num_bigint::BigUint;
+    use serde_json::json;
+    use types::json::{import_gnark, import_snarkjs, ImportError, ImportedGroth16Proof};
+    use types::{
+        ArkworksGroth16Proof, Bn254ArkworksGroth16Proof, Bn254Groth16Proof, Groth16Proof,
+        Groth16VerifyingKey,
+    };
 
     #[test]
     fn test_arkworks_poseidon_groth16_bls12_381() {
+        let (vk, proof, public_inputs) = poseidon_proof(&mut StdRng::seed_from_u64(0u64));
+
+        // Verify the proof
+        let is_valid = Groth16::<ark_bls12_381::Bls12_381, LibsnarkReduction>::verify(
+            &vk,
+            &public_inputs,
+            &proof,
+        )
+        .unwrap();
+        assert!(is_valid);
+
+        let example_proof_payload = ArkworksGroth16Proof::new(&vk, &proof, &public_inputs);
+
+        verify_groth16_proof(
+            &example_proof_payload.vk,
+            &example_proof_payload.proof,
+            &example_proof_payload.deserialize_public_inputs(),
+        )
+        .unwrap();
+    }
+
+    /// Rewrites the proofs the SP1 tests of the prover binary read; run with `--ignored`.
+    #[test]
+    #[ignore]
+    fn regenerate_test_data() {
+        let test_data = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/test_data");
+
+        fs::write(
+            test_data.join("proof.bin"),
+            borsh::to_vec(&bls12_381_payload()).unwrap(),
+        )
+        .unwrap();
+        fs::write(
+            test_data.join("bn254_proof.bin"),
+            borsh::to_vec(&bn254_payload()).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_arkworks_groth16_rejects_malformed_proofs() {
+        let payload = bls12_381_payload();
+        let vk = payload.vk.clone();
+        let proof = payload.proof.clone();
+        let inputs = payload.deserialize_public_inputs();
+
+        assert_eq!(verify_groth16_proof(&vk, &proof, &inputs), Ok(()));
+
+        // missing public input
+        assert_eq!(
+            verify_groth16_proof(&vk, &proof, &[]),
+            Err(Groth16Error::WrongNumberOfPublicInputs {
+                expected: 1,
+                found: 0
+            })
+        );
+
+        // garbage encodings
+        let bad_proof = Groth16Proof {
+            a: [0xff; 48],
+            ..proof.clone()
+        };
+        assert_eq!(
+            verify_groth16_proof(&vk, &bad_proof, &inputs),
+            Err(Groth16Error::InvalidG1Encoding {
+                point: Groth16Point::A
+            })
+        );
+
+        let bad_vk = Groth16VerifyingKey {
+            gamma_g2: [0xff; 96],
+            ..vk.clone()
+        };
+        assert_eq!(
+            verify_groth16_proof(&bad_vk, &proof, &inputs),
+            Err(Groth16Error::InvalidG2Encoding {
+                point: Groth16Point::Gamma
+            })
+        );
+
+        let mut bad_vk = vk.clone();
+        bad_vk.ic[1] = [0xff; 48];
+        assert_eq!(
+            verify_groth16_proof(&bad_vk, &proof, &inputs),
+            Err(Groth16Error::InvalidG1Encoding {
+                point: Groth16Point::Ic(1)
+            })
+        );
+
+        // on-curve points outside the prime-order subgroup
+        let mut a = [0u8; 48];
+        off_subgroup_point::<g1::Config>()
+            .serialize_compressed(&mut a[..])
+            .unwrap();
+        assert_eq!(
+            verify_groth16_proof(&vk, &Groth16Proof { a, ..proof.clone() }, &inputs),
+            Err(Groth16Error::InvalidG1Encoding {
+                point: Groth16Point::A
+            })
+        );
+
+        let mut b = [0u8; 96];
+        off_subgroup_point::<g2::Config>()
+            .serialize_compressed(&mut b[..])
+            .unwrap();
+        assert_eq!(
+            verify_groth16_proof(&vk, &Groth16Proof { b, ..proof.clone() }, &inputs),
+            Err(Groth16Error::InvalidG2Encoding {
+                point: Groth16Point::B
+            })
+        );
+
+        // public input aliasing the valid one modulo the scalar field
+        let modulus = BigUint::from_bytes_le(&Fr::MODULUS.to_bytes_le());
+        let aliased_inputs = vec![&inputs[0] + modulus];
+        assert_eq!(
+            verify_groth16_proof(&vk, &proof, &aliased_inputs),
+            Err(Groth16Error::InvalidPublicInput { index: 0 })
+        );
+
+        // public input the proof wasn't generated for
+        let other_inputs = vec![&inputs[0] + 1u32];
+        assert_eq!(
+            verify_groth16_proof(&vk, &proof, &other_inputs),
+            Err(Groth16Error::PairingFailed)
+        );
+
+        // verifying key the proof wasn't generated for, with valid points
+        let forged_vk = Groth16VerifyingKey {
+            alpha_g1: proof.c,
+            ..vk.clone()
+        };
+        assert_eq!(
+            verify_groth16_proof(&forged_vk, &proof, &inputs),
+            Err(Groth16Error::PairingFailed)
+        );
+        assert_ne!(vk.hash(), forged_vk.hash());
+
+        // forged C, a valid point that breaks the pairing equation
+        let forged = Groth16Proof {
+            c: proof.a,
+            ..proof.clone()
+        };
+        assert_eq!(
+            verify_groth16_proof(&vk, &forged, &inputs),
+            Err(Groth16Error::PairingFailed)
+        );
+    }
+
+    #[test]
+    fn test_arkworks_groth16_batch() {
+        let rng = &mut StdRng::seed_from_u64(0u64);
+        let payload = bls12_381_payload();
+        let inputs = payload.deserialize_public_inputs();
+
+        // distinct valid proofs of the same statement
+        let ark_vk = VerifyingKey::<Bls12_381> {
+            alpha_g1: decompress(&payload.vk.alpha_g1),
+            beta_g2: decompress(&payload.vk.beta_g2),
+            gamma_g2: decompress(&payload.vk.gamma_g2),
+            delta_g2: decompress(&payload.vk.delta_g2),
+            gamma_abc_g1: payload.vk.ic.iter().map(|ic| decompress(ic)).collect(),
+        };
+        let ark_proof = Proof::<Bls12_381> {
+            a: decompress(&payload.proof.a),
+            b: decompress(&payload.proof.b),
+            c: decompress(&payload.proof.c),
+        };
+        let proofs: Vec<Groth16Proof> = (0..16)
+            .map(|_| {
+                let proof = Groth16::<Bls12_381>::rerandomize_proof(&ark_vk, &ark_proof, rng);
+                Groth16Proof::from(&proof)
+            })
+            .collect();
+        let mut batch: Vec<_> = proofs.iter().map(|p| (p, inputs.as_slice())).collect();
+
+        let start_time = Instant::now();
+        for proof in &proofs {
+            assert_eq!(verify_groth16_proof(&payload.vk, proof, &inputs), Ok(()));
+        }
+        println!("Time taken one by one: {:?}", start_time.elapsed());
+
+        let start_time = Instant::now();
+        let pvk = PreparedVerifyingKey::new(&payload.vk).unwrap();
+        for proof in &proofs {
+            assert_eq!(verify_groth16_proof_prepared(&pvk, proof, &inputs), Ok(()));
+        }
+        println!(
+            "Time taken one by one, with a prepared key: {:?}",
+            start_time.elapsed()
+        );
+        assert_eq!(pvk.hash(), payload.vk.hash());
+
+        let start_time = Instant::now();
+        assert_eq!(verify_groth16_batch(&pvk, &batch), Ok(()));
+        println!("Time taken as a batch: {:?}", start_time.elapsed());
+
+        assert_eq!(verify_groth16_batch(&pvk, &[]), Ok(()));
+
+        // a single proof the pairing equation doesn't hold for
+        let forged = Groth16Proof {
+            c: proofs[0].a,
+            ..proofs[1].clone()
+        };
+        let valid = batch[1];
+        batch[1] = (&forged, &inputs);
+        assert_eq!(
+            verify_groth16_batch(&pvk, &batch),
+            Err(Groth16Error::PairingFailed)
+        );
+
+        // invalid proofs whose errors cancel out in a plain sum of the equations
+        let offset = ark_bls12_381::G1Affine::generator();
+        let up = Groth16Proof::from(&Proof::<Bls12_381> {
+            c: (ark_proof.c + offset).into_affine(),
+            ..ark_proof
+        });
+        let down = Groth16Proof::from(&Proof::<Bls12_381> {
+            c: (ark_proof.c - offset).into_affine(),
+            ..ark_proof
+        });
+        batch[1] = (&up, &inputs);
+        batch[2] = (&down, &inputs);
+        assert_eq!(
+            verify_groth16_batch(&pvk, &batch),
+            Err(Groth16Error::PairingFailed)
+        );
+        batch[2] = valid;
+
+        // a public input the proofs weren't generated for
+        let other_inputs = vec![&inputs[0] + 1u32];
+        batch[1] = (&proofs[1], &other_inputs);
+        assert_eq!(
+            verify_groth16_batch(&pvk, &batch),
+            Err(Groth16Error::PairingFailed)
+        );
+
+        // malformed proofs are rejected before the pairing
+        batch[1] = (&proofs[1], &[]);
+        assert_eq!(
+            verify_groth16_batch(&pvk, &batch),
+            Err(Groth16Error::WrongNumberOfPublicInputs {
+                expected: 1,
+                found: 0
+            })
+        );
+
+        let bad_proof = Groth16Proof {
+            a: [0xff; 48],
+            ..proofs[1].clone()
+        };
+        batch[1] = (&bad_proof, &inputs);
+        assert_eq!(
+            verify_groth16_batch(&pvk, &batch),
+            Err(Groth16Error::InvalidG1Encoding {
+                point: Groth16Point::A
+            })
+        );
+    }
+
+    #[test]
+    fn test_arkworks_groth16_bn254() {
+        let (vk, proof, public_inputs) = square_proof::<Bn254>(&mut StdRng::seed_from_u64(0u64));
+        assert!(Groth16::<Bn254, LibsnarkReduction>::verify(&vk, &public_inputs, &proof).unwrap());
+
+        let payload = Bn254ArkworksGroth16Proof::new(&vk, &proof, &public_inputs);
+        let inputs = payload.deserialize_public_inputs();
+        assert_eq!(
+            verify_bn254_groth16_proof(&payload.vk, &payload.proof, &inputs),
+            Ok(())
+        );
+
+        let pvk = Bn254PreparedVerifyingKey::new(&payload.vk).unwrap();
+        assert_eq!(
+            verify_bn254_groth16_proof_prepared(&pvk, &payload.proof, &inputs),
+            Ok(())
+        );
+        assert_eq!(pvk.hash(), payload.vk.hash());
+
+        // public input the proof wasn't generated for
+        let other_inputs = vec![&inputs[0] + 1u32];
+        assert_eq!(
+            verify_bn254_groth16_proof(&payload.vk, &payload.proof, &other_inputs),
+            Err(Groth16Error::PairingFailed)
+        );
+
+        // public input aliasing the valid one modulo the scalar field
+        let modulus = BigUint::from_bytes_le(&FrBn254::MODULUS.to_bytes_le());
+        let aliased_inputs = vec![&inputs[0] + modulus];
+        assert_eq!(
+            verify_bn254_groth16_proof(&payload.vk, &payload.proof, &aliased_inputs),
+            Err(Groth16Error::InvalidPublicInput { index: 0 })
+        );
+
+        // coordinates outside the base field
+        let bad_proof = Bn254Groth16Proof {
+            a: [0xff; 64],
+            ..payload.proof.clone()
+        };
+        assert_eq!(
+            verify_bn254_groth16_proof(&payload.vk, &bad_proof, &inputs),
+            Err(Groth16Error::InvalidG1Encoding {
+                point: Groth16Point::A
+            })
+        );
+
+        // on-curve point outside the prime-order subgroup
+        let bad_proof = Bn254Groth16Proof::from(&Proof::<Bn254> {
+            b: off_subgroup_point::<ark_bn254::g2::Config>(),
+            ..proof
+        });
+        assert_eq!(
+            verify_bn254_groth16_proof(&payload.vk, &bad_proof, &inputs),
+            Err(Groth16Error::InvalidG2Encoding {
+                point: Groth16Point::B
+            })
+        );
+
+        // forged C, a valid point that breaks the pairing equation
+        let forged = Bn254Groth16Proof {
+            c: payload.proof.a,
+            ..payload.proof.clone()
+        };
+        assert_eq!(
+            verify_bn254_groth16_proof(&payload.vk, &forged, &inputs),
+            Err(Groth16Error::PairingFailed)
+        );
+    }
+
+    #[test]
+    fn test_import_snarkjs_and_gnark_groth16() {
         let rng = &mut StdRng::seed_from_u64(0u64);
 
+        let (vk, proof, public_inputs) = square_proof::<Bn254>(rng);
+        let expected = Bn254ArkworksGroth16Proof::new(&vk, &proof, &public_inputs);
+        let snarkjs = snarkjs_json("bn128", &vk, &proof, &public_inputs);
+        let gnark = gnark_json(&vk, &proof, &public_inputs);
+        for imported in [
+            import_snarkjs(&snarkjs.0, &snarkjs.1, &snarkjs.2),
+            import_gnark(&gnark.0, &gnark.1, &gnark.2),
+        ] {
+            let ImportedGroth16Proof::Bn254(payload) = imported.unwrap() else {
+                panic!("expected a BN254 proof");
+            };
+            assert_eq!(payload.vk, expected.vk);
+            assert_eq!(payload.proof, expected.proof);
+            assert_eq!(
+                payload.public_inputs_serialized,
+                expected.public_inputs_serialized
+            );
+            assert_eq!(
+                verify_bn254_groth16_proof(
+                    &payload.vk,
+                    &payload.proof,
+                    &payload.deserialize_public_inputs()
+                ),
+                Ok(())
+            );
+        }
+
+        let (vk, proof, public_inputs) = square_proof::<Bls12_381>(rng);
+        let expected = ArkworksGroth16Proof::new(&vk, &proof, &public_inputs);
+        let snarkjs = snarkjs_json("bls12381", &vk, &proof, &public_inputs);
+        let gnark = gnark_json(&vk, &proof, &public_inputs);
+        for imported in [
+            import_snarkjs(&snarkjs.0, &snarkjs.1, &snarkjs.2),
+            import_gnark(&gnark.0, &gnark.1, &gnark.2),
+        ] {
+            let ImportedGroth16Proof::Bls12_381(payload) = imported.unwrap() else {
+                panic!("expected a BLS12-381 proof");
+            };
+            assert_eq!(payload.vk, expected.vk);
+            assert_eq!(payload.proof, expected.proof);
+            assert_eq!(
+                payload.public_inputs_serialized,
+                expected.public_inputs_serialized
+            );
+            assert_eq!(
+                verify_groth16_proof(
+                    &payload.vk,
+                    &payload.proof,
+                    &payload.deserialize_public_inputs()
+                ),
+                Ok(())
+            );
+        }
+
+        let (vk_json, proof_json, public_json) = snarkjs;
+        let edit = |json: &str, pointer: &str, value: serde_json::Value| {
+            let mut json: serde_json::Value = serde_json::from_str(json).unwrap();
+            *json.pointer_mut(pointer).unwrap() = value;
+            json.to_string()
+        };
+
+        assert!(matches!(
+            import_snarkjs(&vk_json, &proof_json, "[]"),
+            Err(ImportError::WrongNumberOfPublicInputs {
+                expected: 1,
+                found: 0
+            })
+        ));
+        assert!(matches!(
+            import_snarkjs(
+                &edit(&vk_json, "/nPublic", json!(2)),
+                &proof_json,
+                &public_json
+            ),
+            Err(ImportError::WrongNumberOfIcPoints {
+                expected: 3,
+                found: 2
+            })
+        ));
+        assert!(matches!(
+            import_snarkjs(
+                &edit(&vk_json, "/curve", json!("bls12377")),
+                &proof_json,
+                &public_json
+            ),
+            Err(ImportError::UnsupportedCurve { .. })
+        ));
+        assert!(matches!(
+            import_snarkjs(
+                &vk_json,
+                &edit(&proof_json, "/curve", json!("bn128")),
+                &public_json
+            ),
+            Err(ImportError::CurveMismatch { .. })
+        ));
+        assert!(matches!(
+            import_snarkjs(
+                &edit(&vk_json, "/protocol", json!("plonk")),
+                &proof_json,
+                &public_json
+            ),
+            Err(ImportError::UnsupportedProtocol { .. })
+        ));
+
+        // coordinate outside the base field
+        let modulus = BigUint::from_bytes_le(&ark_bls12_381::Fq::MODULUS.to_bytes_le());
+        assert!(matches!(
+            import_snarkjs(
+                &vk_json,
+                &edit(&proof_json, "/pi_a/0", json!(modulus.to_string())),
+                &public_json
+            ),
+            Err(ImportError::InvalidPoint { point }) if point == "pi_a"
+        ));
+
+        let (vk_json, proof_json, public_json) = gnark;
+        assert!(matches!(
+            import_gnark(
+                &edit(&vk_json, "/G1/Alpha/X", json!(1)),
+                &proof_json,
+                &public_json
+            ),
+            Err(ImportError::UnknownCurve)
+        ));
+        assert!(matches!(
+            import_gnark(
+                &vk_json,
+                &edit(&proof_json, "/Commitments", json!([gnark_point(&proof.a)])),
+                &public_json
+            ),
+            Err(ImportError::UnsupportedCommitments)
+        ));
+    }
+
+    #[test]
+    fn test_verifier_parity_with_ark_groth16() {
+        let rng = &mut StdRng::seed_from_u64(0u64);
+
+        let cases = parity_cases::<Bls12_381, _>(rng, 10);
+        for case in &cases {
+            // the mutations must break the proof, or its rejection shows nothing
+            assert_eq!(
+                case.accepted,
+                case.description.starts_with("valid"),
+                "{}",
+                case.description
+            );
+            let payload = ArkworksGroth16Proof::new(&case.vk, &case.proof, &case.public_inputs);
+            let inputs = payload.deserialize_public_inputs();
+            assert_eq!(
+                verify_groth16_proof(&payload.vk, &payload.proof, &inputs).is_ok(),
+                case.accepted,
+                "{}",
+                case.description
+            );
+            let pvk = PreparedVerifyingKey::new(&payload.vk).unwrap();
+            assert_eq!(
+                verify_groth16_batch(&pvk, &[(&payload.proof, inputs.as_slice())]).is_ok(),
+                case.accepted,
+                "{} in a batch",
+                case.description
+            );
+        }
+
+        let bn254_cases = parity_cases::<Bn254, _>(rng, 10);
+        for case in &bn254_cases {
+            // the mutations must break the proof, or its rejection shows nothing
+            assert_eq!(
+                case.accepted,
+                case.description.starts_with("valid"),
+                "{}",
+                case.description
+            );
+            let payload =
+                Bn254ArkworksGroth16Proof::new(&case.vk, &case.proof, &case.public_inputs);
+            let inputs = payload.deserialize_public_inputs();
+            assert_eq!(
+                verify_bn254_groth16_proof(&payload.vk, &payload.proof, &inputs).is_ok(),
+                case.accepted,
+                "BN254 {}",
+                case.description
+            );
+        }
+
+        println!(
+            "{} BLS12-381 and {} BN254 verdicts match ark-groth16",
+            cases.len(),
+            bn254_cases.len()
+        );
+    }
+
+    /// Proves 100 chained Poseidon hashes of 42 over BLS12-381.
+    fn poseidon_proof(
+        rng: &mut StdRng,
+    ) -> (VerifyingKey<Bls12_381>, Proof<Bls12_381>, Vec<FqBLS12_381>) {
         // Create Poseidon parameters with correct dimensions
         let width = 3;
         let full_rounds = 8;
@@ -141,139 +909,154 @@ mod tests {
 
         // The public input is the hash value
         let public_inputs = vec![final_hash];
+        (vk, proof, public_inputs)
+    }
 
-        // Verify the proof
-        let is_valid = Groth16::<ark_bls12_381::Bls12_381, LibsnarkReduction>::verify(
-            &vk,
-            &public_inputs,
-            &proof,
-        )
-        .unwrap();
-        assert!(is_valid);
+    /// The BLS12-381 proof of `proof.bin`.
+    fn bls12_381_payload() -> ArkworksGroth16Proof {
+        let (vk, proof, public_inputs) = poseidon_proof(&mut StdRng::seed_from_u64(0u64));
+        ArkworksGroth16Proof::new(&vk, &proof, &public_inputs)
+    }
 
-        let pi_a = proof.a;
-        let pi_b = proof.b;
-        let pi_c = proof.c;
-        let vk_alpha = vk.alpha_g1;
-        let vk_beta = vk.beta_g2;
-        let vk_gamma = vk.gamma_g2;
-        let vk_delta = vk.delta_g2;
-        let ics = vk.gamma_abc_g1;
-
-        let inputs_as_biguint = convert_381_public_inputs_to_biguint(&public_inputs);
-        let mut ics_serialized: Vec<Vec<u8>> = vec![];
-        for point in ics.clone() {
-            let mut buffer = vec![];
-            point.serialize_compressed(&mut buffer).unwrap();
-            ics_serialized.push(buffer);
-        }
+    /// The BN254 proof of `bn254_proof.bin`.
+    fn bn254_payload() -> Bn254ArkworksGroth16Proof {
+        let (vk, proof, public_inputs) = square_proof::<Bn254>(&mut StdRng::seed_from_u64(0u64));
+        Bn254ArkworksGroth16Proof::new(&vk, &proof, &public_inputs)
+    }
 
-        let mut vk_x: G1 = ics[0];
-        for (idx, ic) in ics.into_iter().enumerate().skip(1) {
-            let ic_coords = extract_g1_coordinates(ic);
-            let ic_scalar: G1 =
-                scalar_mul(ic_coords.0, ic_coords.1, inputs_as_biguint[idx - 1].clone());
-            let ic_scalar_coords = extract_g1_coordinates(ic_scalar);
-            let vk_x_as_coordinates = extract_g1_coordinates(vk_x);
-            let vk_x_as_coords = vk_x_as_coordinates.clone();
-            vk_x = add_g1_as_coordinates(
-                vk_x_as_coords.0,
-                vk_x_as_coords.1,
-                ic_scalar_coords.0,
-                ic_scalar_coords.1,
-            );
-        }
+    fn square_proof<E: Pairing>(
+        rng: &mut StdRng,
+    ) -> (VerifyingKey<E>, Proof<E>, Vec<E::ScalarField>) {
+        let setup_circuit = SquareDemoCircuit {
+            root: Some(E::ScalarField::from(0u32)),
+            square: Some(E::ScalarField::from(0u32)),
+        };
+        let (pk, vk) =
+            Groth16::<E, LibsnarkReduction>::circuit_specific_setup(setup_circuit, rng).unwrap();
 
-        let terms = vec![
-            (negate_g1_affine(pi_a), pi_b),
-            (vk_alpha, vk_beta),
-            (vk_x, vk_gamma),
-            (pi_c, vk_delta),
-        ];
-        let first_point = terms.first().unwrap().0;
-        let mut point_compressed = vec![];
-        first_point
-            .serialize_compressed(&mut point_compressed)
-            .unwrap();
+        let root = E::ScalarField::from(7u32);
+        let proof_circuit = SquareDemoCircuit {
+            root: Some(root),
+            square: Some(root * root),
+        };
+        let proof = Groth16::<E, LibsnarkReduction>::prove(&pk, proof_circuit, rng).unwrap();
+        (vk, proof, vec![root * root])
+    }
 
-        let mut g1_affine_points_serialized: Vec<Vec<u8>> = vec![];
-        let mut g2_affine_points_serialized: Vec<Vec<u8>> = vec![];
+    fn decimal<F: PrimeField>(x: F) -> String {
+        BigUint::from_bytes_be(&x.into_bigint().to_bytes_be()).to_string()
+    }
 
-        for point in terms {
-            let mut point_compressed = vec![];
-            point.0.serialize_compressed(&mut point_compressed).unwrap();
-            g1_affine_points_serialized.push(point_compressed);
+    /// Returns the decimal coordinates of a point, one per base prime field element.
+    fn coordinates<A: AffineRepr>(point: &A) -> [Vec<String>; 2] {
+        let (x, y) = point.xy().unwrap();
+        [x, y].map(|c| c.to_base_prime_field_elements().map(decimal).collect())
+    }
 
-            let mut point_compressed = vec![];
-            point.1.serialize_compressed(&mut point_compressed).unwrap();
-            g2_affine_points_serialized.push(point_compressed);
+    /// Encodes a point like snarkjs, with projective coordinates.
+    fn snarkjs_point<A: AffineRepr>(point: &A) -> serde_json::Value {
+        match coordinates(point) {
+            [x, y] if x.len() == 1 => json!([x[0], y[0], "1"]),
+            [x, y] => json!([x, y, ["1", "0"]]),
         }
+    }
 
-        let is_valid = verify_groth16_proof(
-            g1_affine_points_serialized
-                .iter()
-                .map(|slice| {
-                    let mut arr = [0u8; 48];
-                    arr.copy_from_slice(slice);
-                    arr
-                })
-                .collect::<Vec<[u8; 48]>>(),
-            g2_affine_points_serialized
-                .iter()
-                .map(|slice| {
-                    let mut arr = [0u8; 96];
-                    arr.copy_from_slice(slice);
-                    arr
-                })
-                .collect::<Vec<[u8; 96]>>(),
-            inputs_as_biguint.clone(),
-            ics_serialized.clone(),
-        );
-        assert!(is_valid);
-
-        let example_proof_payload = ArkworksGroth16Proof {
-            g1_affine_points_serialized: g1_affine_points_serialized
-                .iter()
-                .map(|slice| {
-                    let mut arr = [0u8; 48];
-                    arr.copy_from_slice(slice);
-                    arr
-                })
-                .collect::<Vec<[u8; 48]>>(),
-            g2_affine_points_serialized: g2_affine_points_serialized
-                .iter()
-                .map(|slice| {
-                    let mut arr = [0u8; 96];
-                    arr.copy_from_slice(slice);
-                    arr
-                })
-                .collect::<Vec<[u8; 96]>>(),
-            public_inputs_serialized: inputs_as_biguint
-                .iter()
-                .map(|x| x.to_bytes_be())
-                .collect::<Vec<Vec<u8>>>(),
-            ics_input: ics_serialized,
-        };
-
-        let crate_root = env!("CARGO_MANIFEST_DIR");
-        let output_path = Path::new(crate_root).join("src/test_data/proof.bin");
+    /// Returns the contents of snarkjs' `verification_key.json`, `proof.json` and `public.json`.
+    fn snarkjs_json<E: Pairing>(
+        curve: &str,
+        vk: &VerifyingKey<E>,
+        proof: &Proof<E>,
+        public_inputs: &[E::ScalarField],
+    ) -> (String, String, String) {
+        let vk_json = json!({
+            "protocol": "groth16",
+            "curve": curve,
+            "nPublic": public_inputs.len(),
+            "vk_alpha_1": snarkjs_point(&vk.alpha_g1),
+            "vk_beta_2": snarkjs_point(&vk.beta_g2),
+            "vk_gamma_2": snarkjs_point(&vk.gamma_g2),
+            "vk_delta_2": snarkjs_point(&vk.delta_g2),
+            "IC": vk.gamma_abc_g1.iter().map(snarkjs_point).collect::<Vec<_>>(),
+        });
+        let proof_json = json!({
+            "pi_a": snarkjs_point(&proof.a),
+            "pi_b": snarkjs_point(&proof.b),
+            "pi_c": snarkjs_point(&proof.c),
+            "protocol": "groth16",
+            "curve": curve,
+        });
+        let public_json = public_inputs
+            .iter()
+            .map(|x| decimal(*x))
+            .collect::<Vec<_>>();
+        (
+            vk_json.to_string(),
+            proof_json.to_string(),
+            json!(public_json).to_string(),
+        )
+    }
 
-        fs::write(output_path, borsh::to_vec(&example_proof_payload).unwrap()).unwrap();
+    /// Encodes a point like gnark, as `X` and `Y` with the `A0` and `A1` parts in G2.
+    fn gnark_point<A: AffineRepr>(point: &A) -> serde_json::Value {
+        let [x, y] = coordinates(point).map(|c| match c.as_slice() {
+            [c] => json!(c),
+            [a0, a1] => json!({ "A0": a0, "A1": a1 }),
+            _ => unreachable!(),
+        });
+        json!({ "X": x, "Y": y })
     }
 
-    use ark_ff::BigInteger;
-    use ark_ff::PrimeField;
-    use jonas_groth16::verifier::G1;
-    use num_bigint::BigUint;
-    use types::ArkworksGroth16Proof;
+    /// Returns gnark's JSON encodings of the verifying key, the proof and the public witness.
+    fn gnark_json<E: Pairing>(
+        vk: &VerifyingKey<E>,
+        proof: &Proof<E>,
+        public_inputs: &[E::ScalarField],
+    ) -> (String, String, String) {
+        let vk_json = json!({
+            "G1": {
+                "Alpha": gnark_point(&vk.alpha_g1),
+                "K": vk.gamma_abc_g1.iter().map(gnark_point).collect::<Vec<_>>(),
+            },
+            "G2": {
+                "Beta": gnark_point(&vk.beta_g2),
+                "Gamma": gnark_point(&vk.gamma_g2),
+                "Delta": gnark_point(&vk.delta_g2),
+            },
+            "CommitmentKeys": [],
+            "PublicAndCommitmentCommitted": [],
+        });
+        let proof_json = json!({
+            "Ar": gnark_point(&proof.a),
+            "Bs": gnark_point(&proof.b),
+            "Krs": gnark_point(&proof.c),
+            "Commitments": [],
+        });
+        // gnark writes the small field elements as JSON numbers
+        let public_json = public_inputs
+            .iter()
+            .map(|x| {
+                let x = decimal(*x);
+                x.parse::<u64>().map_or(json!(x), |x| json!(x))
+            })
+            .collect::<Vec<_>>();
+        (
+            vk_json.to_string(),
+            proof_json.to_string(),
+            json!(public_json).to_string(),
+        )
+    }
 
-    fn fq381_to_biguint(f: &FqBLS12_381) -> BigUint {
-        let ark_bigint = f.into_bigint();
-        let bytes = ark_bigint.to_bytes_be();
-        BigUint::from_bytes_be(&bytes)
+    fn decompress<P: CanonicalDeserialize>(bytes: &[u8]) -> P {
+        P::deserialize_compressed(bytes).unwrap()
     }
 
-    fn convert_381_public_inputs_to_biguint(inputs: &[FqBLS12_381]) -> Vec<BigUint> {
-        inputs.iter().map(fq381_to_biguint).collect()
+    /// Returns a point on the curve that isn't in the prime-order subgroup.
+    fn off_subgroup_point<P: SWCurveConfig>() -> Affine<P> {
+        (0u64..)
+            .find_map(|x| {
+                Affine::<P>::get_point_from_x_unchecked(P::BaseField::from(x), true)
+                    .filter(|p| !p.is_in_correct_subgroup_assuming_on_curve())
+            })
+            .unwrap()
     }
 }
@@ -6,14 +6,50 @@
 //! ```
 use std::time::Instant;
 
-use sp1_sdk::{include_elf, HashableKey, ProverClient, SP1Stdin};
+use sp1_sdk::{
+    include_elf, HashableKey, ProverClient, SP1ProofWithPublicValues, SP1Stdin, SP1VerifyingKey,
+};
 use sp1_verifier::Groth16Verifier;
 use types::{Sp1Groth16Proof, Sp1Groth16ProofBatch};
 pub const PROVABLE_ELF: &[u8] = include_elf!("provable-program");
 pub const RECURSIVE_ELF: &[u8] = include_elf!("recursive-program");
 pub const RECURSIVE_ARKWORKS_ELF: &[u8] = include_elf!("recursive-arkworks-program");
+pub const RECURSIVE_COMPRESSED_ELF: &[u8] = include_elf!("recursive-compressed-program");
 pub const SIMPLE_MERKLE_PROOF_ELF: &[u8] = include_elf!("simple-merkle-proofs");
-pub const SMT_ZK_PROOF_ELF: &[u8] = include_elf!("smt-opening-proofs");
+pub const SMT_ZK_PROOF_BLAKE3_ELF: &[u8] = include_elf!("smt-opening-proofs-blake3");
+pub const SMT_ZK_PROOF_SHA256_ELF: &[u8] = include_elf!("smt-opening-proofs-sha256");
+pub const SMT_UPDATE_PROOF_ELF: &[u8] = include_elf!("smt-update-proofs");
+pub const HASHER_VECTORS_ELF: &[u8] = include_elf!("hasher-vectors");
+pub const GROTH16_PARITY_ELF: &[u8] = include_elf!("groth16-parity");
+/// The programs whose proofs the recursive program aggregates, all built by `build.rs`.
+pub const INNER_PROGRAM_ELFS: &[&[u8]] = &[
+    PROVABLE_ELF,
+    SIMPLE_MERKLE_PROOF_ELF,
+    SMT_ZK_PROOF_BLAKE3_ELF,
+    SMT_ZK_PROOF_SHA256_ELF,
+    SMT_UPDATE_PROOF_ELF,
+];
+
+/// Returns the verifying key hashes of the inner programs, the allow-list of the recursive program.
+fn allowed_vk_hashes() -> Vec<String> {
+    let client = ProverClient::new();
+    INNER_PROGRAM_ELFS
+        .iter()
+        .map(|elf| client.setup(elf).1.bytes32())
+        .collect()
+}
+
+/// Returns the verifying key digests of the inner programs, the allow-list of the compressed
+/// recursive program.
+#[allow(unused)]
+fn allowed_vk_digests() -> Vec<[u32; 8]> {
+    let client = ProverClient::new();
+    INNER_PROGRAM_ELFS
+        .iter()
+        .map(|elf| client.setup(elf).1.hash_u32())
+        .collect()
+}
+
 #[allow(unused)]
 fn prove_provable_program() -> (Vec<u8>, String, Vec<u8>) {
     // generate a groth16 proof
@@ -31,6 +67,21 @@ fn prove_provable_program() -> (Vec<u8>, String, Vec<u8>) {
     (proof.bytes(), vk_hash, proof.public_values.to_vec())
 }
 
+#[allow(unused)]
+fn prove_provable_program_compressed() -> (SP1ProofWithPublicValues, SP1VerifyingKey) {
+    // generate a compressed proof, to verify with SP1's recursion
+    let client = ProverClient::new();
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&"Hello, Prover!");
+    let (pk, vk) = client.setup(PROVABLE_ELF);
+    let proof = client
+        .prove(&pk, &stdin)
+        .compressed()
+        .run()
+        .expect("failed to generate compressed provable_program proof");
+    (proof, vk)
+}
+
 fn main() {
     let start_time = Instant::now();
     sp1_sdk::utils::setup_logger();
@@ -40,13 +91,12 @@ fn main() {
     // verify a groth16 proof inside the circuit
     let client = ProverClient::new();
     let mut stdin = SP1Stdin::new();
-    let inputs = Sp1Groth16ProofBatch {
-        proofs: vec![Sp1Groth16Proof {
-            proof,
-            vk_hash,
-            public_values,
-        }],
-    };
+    let mut inputs = Sp1Groth16ProofBatch::new(allowed_vk_hashes());
+    inputs.proofs.push(Sp1Groth16Proof {
+        proof,
+        vk_hash,
+        public_values,
+    });
     stdin.write_vec(borsh::to_vec(&inputs).unwrap());
 
     let (pk, vk) = client.setup(RECURSIVE_ELF);
@@ -72,14 +122,26 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sp1_sdk::Prover;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use prover::arkworks::parity_cases;
+    use sp1_sdk::{Prover, SP1Proof};
     use sp1_verifier::Groth16Verifier;
     use std::{fs, path::Path, time::Instant};
     use types::{
-        ArkworksGroth16Proof, ArkworksGroth16ProofBatch, MockMerkleProofBatch, SmtOpeningBatch,
-        SmtOpeningInput, Sp1Groth16Proof, Sp1Groth16ProofBatch,
+        hash_smt_operations, ArkworksGroth16Output, ArkworksGroth16Proof,
+        ArkworksGroth16ProofBatch, Bn254ArkworksGroth16Proof, Groth16ParityCases,
+        Groth16ParityOutput, HasherVectorsOutput, MockMerkleProofBatch, RecursiveOutput,
+        SmtOpeningBatch, SmtOpeningInput, SmtOpeningOutput, SmtOperation, SmtUpdateInput,
+        SmtUpdateOutput, Sp1CompressedProof, Sp1CompressedProofBatch, Sp1Groth16Proof,
+        Sp1Groth16ProofBatch,
+    };
+    use valence_coprocessor_core::{
+        check_hasher_vectors, Blake3Context, ExecutionContext, Hash, Hasher, Sha2Context,
+        HASHER_VECTORS,
     };
-    use valence_smt::MemorySmt;
+    use valence_smt::{MemoryBackend, MemorySmt, RecordingBackend, Smt};
 
     #[test]
     fn test_wrapper_merkle_proof_single() {
@@ -91,13 +153,12 @@ mod tests {
         // verify a groth16 proof inside the circuit
         let client = ProverClient::new();
         let mut stdin = SP1Stdin::new();
-        let inputs = Sp1Groth16ProofBatch {
-            proofs: vec![Sp1Groth16Proof {
-                proof,
-                vk_hash,
-                public_values,
-            }],
-        };
+        let mut inputs = Sp1Groth16ProofBatch::new(allowed_vk_hashes());
+        inputs.proofs.push(Sp1Groth16Proof {
+            proof,
+            vk_hash,
+            public_values,
+        });
         stdin.write_vec(borsh::to_vec(&inputs).unwrap());
 
         let (pk, vk) = client.setup(RECURSIVE_ELF);
@@ -116,6 +177,7 @@ mod tests {
             groth16_vk,
         )
         .unwrap();
+        check_recursive_output(&inputs, proof.public_values.as_slice());
         let end_time = Instant::now() - start_time;
         println!("Time taken: {:?}", end_time);
     }
@@ -126,7 +188,7 @@ mod tests {
         sp1_sdk::utils::setup_logger();
         let client = ProverClient::new();
         let mut stdin = SP1Stdin::new();
-        let mut inputs: Sp1Groth16ProofBatch = Sp1Groth16ProofBatch { proofs: Vec::new() };
+        let mut inputs = Sp1Groth16ProofBatch::new(allowed_vk_hashes());
         let (proof, vk_hash, public_values) = prove_provable_program();
         for _ in 0..9 {
             // generate the same opening groth16 proof 10 times
@@ -153,10 +215,136 @@ mod tests {
             groth16_vk,
         )
         .unwrap();
+        check_recursive_output(&inputs, proof.public_values.as_slice());
         let end_time = Instant::now() - start_time;
         println!("Time taken: {:?}", end_time);
     }
 
+    #[test]
+    fn test_recursive_program_rejects_unlisted_programs() {
+        let (proof, vk_hash, public_values) = prove_provable_program();
+
+        // every inner program but the one of the proof
+        let allowed = allowed_vk_hashes()
+            .into_iter()
+            .filter(|allowed| *allowed != vk_hash)
+            .collect();
+        let mut inputs = Sp1Groth16ProofBatch::new(allowed);
+        inputs.proofs.push(Sp1Groth16Proof {
+            proof,
+            vk_hash,
+            public_values,
+        });
+        let mut stdin = SP1Stdin::new();
+        stdin.write_vec(borsh::to_vec(&inputs).unwrap());
+
+        // execute only; the guest panics before committing anything
+        let client = ProverClient::new();
+        assert!(client.execute(RECURSIVE_ELF, &stdin).run().is_err());
+    }
+
+    #[test]
+    fn test_recursive_aggregation_benchmark() {
+        sp1_sdk::utils::setup_logger();
+        let proof_count = 2;
+        let client = ProverClient::new();
+
+        // Groth16 inner proofs, verified with sp1-verifier in the guest
+        let start_time = Instant::now();
+        let mut groth16_batch = Sp1Groth16ProofBatch::new(allowed_vk_hashes());
+        for _ in 0..proof_count {
+            let (proof, vk_hash, public_values) = prove_provable_program();
+            groth16_batch.proofs.push(Sp1Groth16Proof {
+                proof,
+                vk_hash,
+                public_values,
+            });
+        }
+        let groth16_inner_time = start_time.elapsed();
+        let mut stdin = SP1Stdin::new();
+        stdin.write_vec(borsh::to_vec(&groth16_batch).unwrap());
+        let (_, report) = client
+            .execute(RECURSIVE_ELF, &stdin)
+            .run()
+            .expect("failed to execute the recursive program");
+        let groth16_cycles = report.total_instruction_count();
+        let (pk, _) = client.setup(RECURSIVE_ELF);
+        let start_time = Instant::now();
+        let proof = client
+            .prove(&pk, &stdin)
+            .groth16()
+            .run()
+            .expect("failed to generate recursive proof");
+        let groth16_outer_time = start_time.elapsed();
+        check_recursive_output(&groth16_batch, proof.public_values.as_slice());
+
+        // compressed inner proofs, verified with SP1's recursion
+        let start_time = Instant::now();
+        let mut compressed_batch = Sp1CompressedProofBatch::new(allowed_vk_digests());
+        let mut inner_proofs = Vec::new();
+        for _ in 0..proof_count {
+            let (proof, vk) = prove_provable_program_compressed();
+            compressed_batch.proofs.push(Sp1CompressedProof {
+                vk_digest: vk.hash_u32(),
+                public_values: proof.public_values.to_vec(),
+            });
+            inner_proofs.push((proof, vk));
+        }
+        let compressed_inner_time = start_time.elapsed();
+        let mut stdin = SP1Stdin::new();
+        stdin.write_vec(borsh::to_vec(&compressed_batch).unwrap());
+        for (proof, vk) in inner_proofs {
+            let SP1Proof::Compressed(proof) = proof.proof else {
+                panic!("expected a compressed proof");
+            };
+            stdin.write_proof(*proof, vk.vk);
+        }
+        let (_, report) = client
+            .execute(RECURSIVE_COMPRESSED_ELF, &stdin)
+            .run()
+            .expect("failed to execute the compressed recursive program");
+        let compressed_cycles = report.total_instruction_count();
+        let (pk, _) = client.setup(RECURSIVE_COMPRESSED_ELF);
+        let start_time = Instant::now();
+        let proof = client
+            .prove(&pk, &stdin)
+            .groth16()
+            .run()
+            .expect("failed to generate compressed recursive proof");
+        let compressed_outer_time = start_time.elapsed();
+
+        let output = RecursiveOutput::decode(proof.public_values.as_slice()).unwrap();
+        assert_eq!(
+            output,
+            RecursiveOutput::for_compressed_batch(&compressed_batch)
+        );
+        for (index, proof) in compressed_batch.proofs.iter().enumerate() {
+            let siblings = compressed_batch.inclusion_proof(index);
+            assert!(output.includes_compressed(proof, index, &siblings));
+        }
+
+        // both aggregates are wrapped in Groth16, so only the inner proofs and their checks differ
+        println!("Aggregation of {proof_count} proofs");
+        println!("| Mode | Inner proofs | Guest cycles | Aggregate proof |");
+        println!("|---|---|---|---|");
+        println!(
+            "| Groth16 in guest | {groth16_inner_time:?} | {groth16_cycles} | {groth16_outer_time:?} |"
+        );
+        println!(
+            "| Compressed | {compressed_inner_time:?} | {compressed_cycles} | {compressed_outer_time:?} |"
+        );
+    }
+
+    /// Checks the recursive program committed the programs and outputs of every inner proof.
+    fn check_recursive_output(batch: &Sp1Groth16ProofBatch, public_values: &[u8]) {
+        let output = RecursiveOutput::decode(public_values).unwrap();
+        assert_eq!(output, RecursiveOutput::for_batch(batch));
+        for (index, proof) in batch.proofs.iter().enumerate() {
+            let siblings = batch.inclusion_proof(index);
+            assert!(output.includes(proof, index, &siblings));
+        }
+    }
+
     #[test]
     fn test_arkworks_groth16_proof_batch() {
         let start_time = Instant::now();
@@ -165,27 +353,129 @@ mod tests {
         let mut stdin = SP1Stdin::new();
         let proof_serialized = fs::read(output_path).unwrap();
 
-        let mut proof_batch: ArkworksGroth16ProofBatch =
-            ArkworksGroth16ProofBatch { proofs: vec![] };
+        let mut proof_batch = ArkworksGroth16ProofBatch::default();
         // recursively verify a batch of proofs
         for _ in 0..200 {
             let proof: ArkworksGroth16Proof = borsh::from_slice(&proof_serialized).unwrap();
-            proof_batch.proofs.push(proof);
+            proof_batch.push(proof);
         }
         let circuit_input = borsh::to_vec(&proof_batch).unwrap();
         stdin.write_vec(circuit_input);
         let client = ProverClient::builder().network().build();
 
         let (pk, vk) = client.setup(RECURSIVE_ARKWORKS_ELF);
-        let _ = client
+        let proof = client
             .prove(&pk, &stdin)
             .groth16()
             .run()
             .expect("failed to generate recursive proof");
+        let output = ArkworksGroth16Output::decode(proof.public_values.as_slice()).unwrap();
+        assert_eq!(output, ArkworksGroth16Output::for_batch(&proof_batch));
         let end_time = Instant::now() - start_time;
         println!("Time taken to prove: {:?}", end_time);
     }
 
+    #[test]
+    fn test_arkworks_groth16_cycles() {
+        let crate_root = env!("CARGO_MANIFEST_DIR");
+        let proof_serialized =
+            fs::read(Path::new(crate_root).join("src/test_data/proof.bin")).unwrap();
+        let bn254_proof_serialized =
+            fs::read(Path::new(crate_root).join("src/test_data/bn254_proof.bin")).unwrap();
+
+        // a batch of a single proof costs about as much as verifying the proof on its own
+        for proof_count in [1, 10] {
+            let mut proof_batch = ArkworksGroth16ProofBatch::default();
+            for _ in 0..proof_count {
+                proof_batch.push(borsh::from_slice(&proof_serialized).unwrap());
+                proof_batch.push_bn254(borsh::from_slice(&bn254_proof_serialized).unwrap());
+            }
+            // the key of the proofs is only listed once
+            assert_eq!(proof_batch.vks.len(), 1);
+            assert_eq!(proof_batch.bn254_vks.len(), 1);
+            let vk_hash = proof_batch.vks[0].hash();
+            let bn254_vk_hash = proof_batch.bn254_vks[0].hash();
+            let mut stdin = SP1Stdin::new();
+            stdin.write_vec(borsh::to_vec(&proof_batch).unwrap());
+
+            // execute only; the cycle counts don't depend on the proving backend
+            let client = ProverClient::new();
+            let (public_values, report) = client
+                .execute(RECURSIVE_ARKWORKS_ELF, &stdin)
+                .run()
+                .expect("failed to execute the recursive arkworks program");
+            let output = ArkworksGroth16Output::decode(public_values.as_slice()).unwrap();
+            assert_eq!(output.vk_hashes, vec![vk_hash; proof_count]);
+            assert_eq!(output.bn254_vk_hashes, vec![bn254_vk_hash; proof_count]);
+            assert_eq!(output.proof_count, 2 * proof_count as u64);
+            // the committed statements are the ones of the batch
+            assert_eq!(output, ArkworksGroth16Output::for_batch(&proof_batch));
+
+            let verify_cycles = report.cycle_tracker["verify_groth16_batch"];
+            let msm_cycles = report.cycle_tracker["msm"];
+            // A and C, then B, of every proof; alpha and the IC points, then beta, gamma and delta
+            let g1_cycles = report.cycle_tracker["decode_g1"] / (2 * proof_count as u64);
+            let g2_cycles = report.cycle_tracker["decode_g2"] / proof_count as u64;
+            let vk_g1_count = 1 + proof_batch.vks[0].ic.len() as u64;
+            let vk_g1_cycles = report.cycle_tracker["decode_vk_g1"] / vk_g1_count;
+            let vk_g2_cycles = report.cycle_tracker["decode_vk_g2"] / 3;
+            let bn254_verify_cycles = report.cycle_tracker["verify_bn254_groth16_proof"];
+            println!("Batch of {proof_count} proofs per curve");
+            println!("Total cycles: {}", report.total_instruction_count());
+            println!(
+                "Cycles per BLS12-381 verification: {}",
+                verify_cycles / proof_count as u64
+            );
+            println!(
+                "Cycles of the BLS12-381 vk_x MSM per proof: {}",
+                msm_cycles / proof_count as u64
+            );
+            println!("Cycles per checked G1 and G2 proof point: {g1_cycles}, {g2_cycles}");
+            println!("Cycles per unchecked G1 and G2 key point: {vk_g1_cycles}, {vk_g2_cycles}");
+            println!(
+                "Cycles per BN254 verification: {}",
+                bn254_verify_cycles / proof_count as u64
+            );
+        }
+    }
+
+    #[test]
+    fn test_groth16_parity_in_guest() {
+        let rng = &mut StdRng::seed_from_u64(0u64);
+        let cases = parity_cases::<Bls12_381, _>(rng, 3);
+        let bn254_cases = parity_cases::<Bn254, _>(rng, 3);
+        let input = Groth16ParityCases {
+            proofs: cases
+                .iter()
+                .map(|c| ArkworksGroth16Proof::new(&c.vk, &c.proof, &c.public_inputs))
+                .collect(),
+            bn254_proofs: bn254_cases
+                .iter()
+                .map(|c| Bn254ArkworksGroth16Proof::new(&c.vk, &c.proof, &c.public_inputs))
+                .collect(),
+        };
+        let mut stdin = SP1Stdin::new();
+        stdin.write_vec(borsh::to_vec(&input).unwrap());
+
+        // execute only; the verdicts of the precompile backed verifier are what we compare
+        let client = ProverClient::new();
+        let (public_values, _) = client
+            .execute(GROTH16_PARITY_ELF, &stdin)
+            .run()
+            .expect("failed to execute the groth16 parity program");
+        let output: Groth16ParityOutput = borsh::from_slice(public_values.as_slice()).unwrap();
+
+        let verdicts: Vec<bool> = cases.iter().map(|c| c.accepted).collect();
+        assert_eq!(
+            output,
+            Groth16ParityOutput {
+                batch_verdicts: verdicts.clone(),
+                verdicts,
+                bn254_verdicts: bn254_cases.iter().map(|c| c.accepted).collect(),
+            }
+        );
+    }
+
     #[test]
     fn test_simple_merkle_proof_batch() {
         let start_time = Instant::now();
@@ -224,20 +514,18 @@ mod tests {
         println!("Time taken: {:?}", end_time);
     }
 
-    #[test]
-    fn test_smt_zk_proof_batch() {
-        let proof_count = 254;
-        let start_time = Instant::now();
-
-        let client = ProverClient::new();
-        let mut stdin = SP1Stdin::new();
+    fn smt_opening_batch<C>(proof_count: usize) -> (SmtOpeningBatch, [u8; 32])
+    where
+        C: ExecutionContext,
+        C::Hasher: Hasher<Output = Hash>,
+    {
         let context = "poem";
         let mut data: Vec<[u8; 3]> = vec![[0x00, 0x00, 0x00]];
         for i in 1..proof_count {
             data.push([0x00, 0x00, i as u8]);
         }
 
-        let mut tree = MemorySmt::default();
+        let mut tree = Smt::<MemoryBackend, C>::default();
 
         let mut inserts = 0;
         let mut root = [0; 32];
@@ -263,10 +551,22 @@ mod tests {
         }
         assert_eq!(proof_batch.proofs.len(), proof_count);
 
+        (proof_batch, root)
+    }
+
+    #[test]
+    fn test_smt_zk_proof_batch() {
+        let proof_count = 254;
+        let start_time = Instant::now();
+
+        let client = ProverClient::new();
+        let mut stdin = SP1Stdin::new();
+        let (proof_batch, _) = smt_opening_batch::<Sha2Context>(proof_count);
+
         let proof_batch_serialized = borsh::to_vec(&proof_batch).unwrap();
         stdin.write_vec(proof_batch_serialized);
 
-        let (pk, _) = client.setup(SMT_ZK_PROOF_ELF);
+        let (pk, _) = client.setup(SMT_ZK_PROOF_SHA256_ELF);
         let _ = client
             .prove(&pk, &stdin)
             .groth16()
@@ -275,4 +575,122 @@ mod tests {
         let end_time = Instant::now() - start_time;
         println!("Time taken: {:?}", end_time);
     }
+
+    fn check_smt_opening_roots<C>(elf: &[u8])
+    where
+        C: ExecutionContext,
+        C::Hasher: Hasher<Output = Hash>,
+    {
+        let proof_count = 16;
+        let (proof_batch, root) = smt_opening_batch::<C>(proof_count);
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write_vec(borsh::to_vec(&proof_batch).unwrap());
+
+        let client = ProverClient::new();
+        let (public_values, _) = client
+            .execute(elf, &stdin)
+            .run()
+            .expect("failed to execute smt merkle proof batch");
+        let output: SmtOpeningOutput = borsh::from_slice(public_values.as_slice()).unwrap();
+
+        assert_eq!(output.roots, vec![root; proof_count]);
+    }
+
+    #[test]
+    fn test_smt_opening_roots_match_host() {
+        check_smt_opening_roots::<Blake3Context>(SMT_ZK_PROOF_BLAKE3_ELF);
+        check_smt_opening_roots::<Sha2Context>(SMT_ZK_PROOF_SHA256_ELF);
+    }
+
+    #[test]
+    fn test_smt_update_proof() {
+        let context = "poem";
+        let mut tree = MemorySmt::default();
+        let mut root = MemorySmt::empty_tree_root();
+        for i in 0..32u8 {
+            root = tree.insert(root, context, vec![0x00, 0x00, i]).unwrap();
+        }
+
+        // dry-run the operations on the host, recording every node they read
+        let operations = vec![
+            SmtOperation::Get {
+                context: context.to_string(),
+                data: vec![0x00, 0x00, 0x07],
+            },
+            SmtOperation::Insert {
+                context: context.to_string(),
+                data: vec![0x00, 0x01, 0x00],
+            },
+            SmtOperation::Insert {
+                context: context.to_string(),
+                data: vec![0x00, 0x01, 0x01],
+            },
+            SmtOperation::Get {
+                context: context.to_string(),
+                data: vec![0x00, 0x01, 0x00],
+            },
+        ];
+        let mut host = Smt::<_, Blake3Context>::from(RecordingBackend::new(tree.into_backend()));
+        let mut updated_root = root;
+        for operation in &operations {
+            match operation {
+                SmtOperation::Get { context, data } => {
+                    host.get_opening(context, updated_root, data)
+                        .unwrap()
+                        .unwrap();
+                }
+                SmtOperation::Insert { context, data } => {
+                    updated_root = host.insert(updated_root, context, data.clone()).unwrap();
+                }
+            }
+        }
+
+        let operations_hash = hash_smt_operations(&operations);
+        let input = SmtUpdateInput {
+            witness_serialized: borsh::to_vec(&host.backend().witness()).unwrap(),
+            root,
+            operations,
+        };
+        let mut stdin = SP1Stdin::new();
+        stdin.write_vec(borsh::to_vec(&input).unwrap());
+
+        let client = ProverClient::new();
+        let (public_values, _) = client
+            .execute(SMT_UPDATE_PROOF_ELF, &stdin)
+            .run()
+            .expect("failed to execute smt update program");
+        let output: SmtUpdateOutput = borsh::from_slice(public_values.as_slice()).unwrap();
+
+        assert_eq!(
+            output,
+            SmtUpdateOutput {
+                root,
+                updated_root,
+                operations_hash,
+            }
+        );
+    }
+
+    #[test]
+    fn test_hasher_vectors_match_guest() {
+        let report = check_hasher_vectors(HASHER_VECTORS).unwrap();
+        assert_eq!(report.skipped, 0);
+
+        // execute only; the vectors must hold in the zkVM, proving adds nothing here
+        let client = ProverClient::new();
+        let (public_values, _) = client
+            .execute(HASHER_VECTORS_ELF, &SP1Stdin::new())
+            .run()
+            .expect("failed to execute the hasher vectors program");
+        let output: HasherVectorsOutput = borsh::from_slice(public_values.as_slice()).unwrap();
+
+        assert_eq!(
+            output,
+            HasherVectorsOutput {
+                checked: report.checked as u64,
+                skipped: 0,
+            }
+        );
+    }
 }
@@ -1,17 +1,86 @@
 #![no_main]
 sp1_zkvm::entrypoint!(main);
-use jonas_groth16::verifier::verify_groth16_proof;
-use types::ArkworksGroth16ProofBatch;
+use jonas_groth16::bn254::{
+    verify_groth16_proof_prepared as verify_bn254_groth16_proof,
+    PreparedVerifyingKey as Bn254PreparedVerifyingKey,
+};
+use jonas_groth16::verifier::{verify_groth16_batch, PreparedVerifyingKey};
+use types::{hash_public_inputs, ArkworksGroth16Output, ArkworksGroth16ProofBatch};
 pub fn main() {
-    let proofs: ArkworksGroth16ProofBatch = borsh::from_slice(&sp1_zkvm::io::read_vec()).unwrap();
-    for proof in proofs.proofs {
-        let public_inputs = proof.deserialize_public_inputs();
-        let result = verify_groth16_proof(
-            proof.g1_affine_points_serialized,
-            proof.g2_affine_points_serialized,
-            public_inputs,
-            proof.ics_input,
-        );
-        assert!(result);
+    let batch: ArkworksGroth16ProofBatch = borsh::from_slice(&sp1_zkvm::io::read_vec()).unwrap();
+
+    // every key is decoded and prepared once, however many proofs reference it
+    println!("cycle-tracker-report-start: prepare_verifying_keys");
+    let pvks: Vec<_> = batch
+        .vks
+        .iter()
+        .enumerate()
+        .map(|(i, vk)| {
+            PreparedVerifyingKey::new(vk)
+                .unwrap_or_else(|e| panic!("Verifying key {i} of the batch is invalid: {e}"))
+        })
+        .collect();
+    let bn254_pvks: Vec<_> = batch
+        .bn254_vks
+        .iter()
+        .enumerate()
+        .map(|(i, vk)| {
+            Bn254PreparedVerifyingKey::new(vk)
+                .unwrap_or_else(|e| panic!("BN254 verifying key {i} of the batch is invalid: {e}"))
+        })
+        .collect();
+    println!("cycle-tracker-report-end: prepare_verifying_keys");
+
+    // proofs sharing a verifying key are checked together, with a single final exponentiation
+    let mut groups = vec![vec![]; pvks.len()];
+    let mut vk_hashes = Vec::with_capacity(batch.proofs.len());
+    let mut public_inputs = Vec::with_capacity(batch.proofs.len() + batch.bn254_proofs.len());
+    for (i, proof) in batch.proofs.iter().enumerate() {
+        let vk_index = proof.vk_index as usize;
+        let Some(group) = groups.get_mut(vk_index) else {
+            panic!("Proof {i} of the batch references a missing verifying key");
+        };
+        group.push(i);
+        // commit the key each proof was verified against
+        vk_hashes.push(pvks[vk_index].hash());
+        public_inputs.push(proof.deserialize_public_inputs());
     }
+    // the cycles of every verification are summed up in the execution report
+    println!("cycle-tracker-report-start: verify_groth16_batch");
+    for (i, (pvk, indices)) in pvks.iter().zip(&groups).enumerate() {
+        let proofs: Vec<_> = indices
+            .iter()
+            .map(|&j| (&batch.proofs[j].proof, public_inputs[j].as_slice()))
+            .collect();
+        // a single invalid proof invalidates the whole batch
+        if let Err(e) = verify_groth16_batch(pvk, &proofs) {
+            panic!("The proofs of verifying key {i} of the batch are invalid: {e}");
+        }
+    }
+    println!("cycle-tracker-report-end: verify_groth16_batch");
+
+    let mut bn254_vk_hashes = Vec::with_capacity(batch.bn254_proofs.len());
+    for (i, proof) in batch.bn254_proofs.iter().enumerate() {
+        let Some(pvk) = bn254_pvks.get(proof.vk_index as usize) else {
+            panic!("BN254 proof {i} of the batch references a missing verifying key");
+        };
+        let inputs = proof.deserialize_public_inputs();
+        println!("cycle-tracker-report-start: verify_bn254_groth16_proof");
+        let result = verify_bn254_groth16_proof(pvk, &proof.proof, &inputs);
+        println!("cycle-tracker-report-end: verify_bn254_groth16_proof");
+        if let Err(e) = result {
+            panic!("BN254 proof {i} of the batch is invalid: {e}");
+        }
+        bn254_vk_hashes.push(pvk.hash());
+        public_inputs.push(inputs);
+    }
+
+    // commit the statements, so the aggregate proof says which inputs were proven under which keys
+    let output = ArkworksGroth16Output {
+        vk_hashes,
+        bn254_vk_hashes,
+        public_inputs_hash: hash_public_inputs(public_inputs.iter().map(Vec::as_slice)),
+        proof_count: public_inputs.len() as u64,
+    };
+    sp1_zkvm::io::commit_slice(&borsh::to_vec(&output).unwrap());
 }
@@ -0,0 +1,22 @@
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+use sha2::{Digest, Sha256};
+use sp1_zkvm::lib::verify::verify_sp1_proof;
+use types::{RecursiveOutput, Sp1CompressedProofBatch};
+
+pub fn main() {
+    let proofs: Sp1CompressedProofBatch = borsh::from_slice(&sp1_zkvm::io::read_vec()).unwrap();
+    for (i, proof) in proofs.proofs.iter().enumerate() {
+        if !proofs.allowed_vk_digests.contains(&proof.vk_digest) {
+            panic!("Proof {i} of the batch is from a program outside the allow-list");
+        }
+        // the proofs are written to the stdin by the host and checked by SP1's recursion, which
+        // binds them to these keys and public values
+        let public_values_digest: [u8; 32] = Sha256::digest(&proof.public_values).into();
+        verify_sp1_proof(&proof.vk_digest, &public_values_digest);
+    }
+
+    // the same output as the Groth16 recursive program, with the programs named by their digests
+    let output = RecursiveOutput::for_compressed_batch(&proofs);
+    sp1_zkvm::io::commit_slice(&borsh::to_vec(&output).unwrap());
+}
@@ -1,12 +1,16 @@
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 use sp1_verifier::Groth16Verifier;
-use types::Sp1Groth16ProofBatch;
+use types::{RecursiveOutput, Sp1Groth16ProofBatch};
 
 pub fn main() {
     let proofs: Sp1Groth16ProofBatch = borsh::from_slice(&sp1_zkvm::io::read_vec()).unwrap();
     let groth16_vk = *sp1_verifier::GROTH16_VK_BYTES;
-    for proof in proofs.proofs {
+    for (i, proof) in proofs.proofs.iter().enumerate() {
+        // the allow-list is committed, so only the listed programs can be aggregated
+        if !proofs.allowed_vk_hashes.contains(&proof.vk_hash) {
+            panic!("Proof {i} of the batch is from a program outside the allow-list");
+        }
         Groth16Verifier::verify(
             &proof.proof,
             &proof.public_values,
@@ -15,4 +19,8 @@ pub fn main() {
         )
         .expect("Failed to verify proof!");
     }
+
+    // commit which programs were verified and a root over their outputs
+    let output = RecursiveOutput::for_batch(&proofs);
+    sp1_zkvm::io::commit_slice(&borsh::to_vec(&output).unwrap());
 }
@@ -0,0 +1,7 @@
+#![no_main]
+
+use valence_coprocessor_core::Blake3Context;
+sp1_zkvm::entrypoint!(main);
+pub fn main() {
+    smt_opening_proofs::run::<Blake3Context>();
+}
@@ -0,0 +1,8 @@
+#![no_main]
+
+use valence_coprocessor_core::Sha2Context;
+sp1_zkvm::entrypoint!(main);
+pub fn main() {
+    // the sha2 crate is patched in the workspace so hashing goes through the SP1 precompile
+    smt_opening_proofs::run::<Sha2Context>();
+}
@@ -0,0 +1,35 @@
+use types::{SmtOpeningBatch, SmtOpeningOutput};
+use valence_coprocessor_core::{ExecutionContext, Hash, Hasher};
+use valence_smt::{MemoryBackend, SmtOpening};
+
+type Smt<C> = valence_smt::Smt<MemoryBackend, C>;
+
+/// Verifies every opening of the batch under the execution context `C`, returning the roots the
+/// openings resolve to.
+pub fn verify_batch<C>(proof_batch: &SmtOpeningBatch) -> SmtOpeningOutput
+where
+    C: ExecutionContext,
+    C::Hasher: Hasher<Output = Hash>,
+{
+    let mut roots = Vec::with_capacity(proof_batch.proofs.len());
+    for proof in &proof_batch.proofs {
+        let opening: SmtOpening = borsh::from_slice(&proof.proof_serialized).unwrap();
+        let root = Smt::<C>::compute_root(&proof.context, &opening);
+        // assert that the proof is valid for the corresponding root
+        assert_eq!(root, proof.root);
+        roots.push(root);
+    }
+    SmtOpeningOutput { roots }
+}
+
+/// Reads a batch from the zkVM input, verifies it under the execution context `C` and commits
+/// the computed roots.
+pub fn run<C>()
+where
+    C: ExecutionContext,
+    C::Hasher: Hasher<Output = Hash>,
+{
+    let proof_batch: SmtOpeningBatch = borsh::from_slice(&sp1_zkvm::io::read_vec()).unwrap();
+    let output = verify_batch::<C>(&proof_batch);
+    sp1_zkvm::io::commit_slice(&borsh::to_vec(&output).unwrap());
+}
@@ -0,0 +1,41 @@
+#![no_main]
+
+use types::{hash_smt_operations, SmtOperation, SmtUpdateInput, SmtUpdateOutput};
+use valence_coprocessor_core::Blake3Context;
+use valence_smt::{Smt, WitnessBackend};
+sp1_zkvm::entrypoint!(main);
+pub fn main() {
+    let input: SmtUpdateInput = borsh::from_slice(&sp1_zkvm::io::read_vec()).unwrap();
+    let backend = WitnessBackend::try_from_slice::<Blake3Context>(&input.witness_serialized)
+        .expect("Failed to load the witness!");
+    let mut tree = Smt::<_, Blake3Context>::from(backend);
+    let mut root = input.root;
+    let operations_hash = hash_smt_operations(&input.operations);
+
+    for operation in input.operations {
+        match operation {
+            SmtOperation::Get { context, data } => {
+                // assert that the data is part of the current root
+                let proof = tree
+                    .get_opening(&context, root, &data)
+                    .expect("Failed to open the witness!")
+                    .expect("The data is not part of the tree!");
+                assert!(Smt::<WitnessBackend, Blake3Context>::verify(
+                    &context, &root, &proof
+                ));
+            }
+            SmtOperation::Insert { context, data } => {
+                root = tree
+                    .insert(root, &context, data)
+                    .expect("Failed to insert into the witness!");
+            }
+        }
+    }
+
+    let output = SmtUpdateOutput {
+        root: input.root,
+        updated_root: root,
+        operations_hash,
+    };
+    sp1_zkvm::io::commit_slice(&borsh::to_vec(&output).unwrap());
+}
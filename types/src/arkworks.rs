@@ -0,0 +1,119 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_bn254::Bn254;
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Proof, VerifyingKey};
+use ark_serialize::CanonicalSerialize;
+
+use crate::{
+    ArkworksGroth16Proof, Bn254ArkworksGroth16Proof, Bn254Groth16Proof, Bn254Groth16VerifyingKey,
+    Groth16Proof, Groth16VerifyingKey,
+};
+
+fn compress<P: CanonicalSerialize, const N: usize>(point: &P) -> [u8; N] {
+    let mut buffer = [0u8; N];
+    point
+        .serialize_compressed(&mut buffer[..])
+        .expect("the compressed point size is fixed by the curve");
+    buffer
+}
+
+impl From<&VerifyingKey<Bls12_381>> for Groth16VerifyingKey {
+    fn from(vk: &VerifyingKey<Bls12_381>) -> Self {
+        Self {
+            alpha_g1: compress(&vk.alpha_g1),
+            beta_g2: compress(&vk.beta_g2),
+            gamma_g2: compress(&vk.gamma_g2),
+            delta_g2: compress(&vk.delta_g2),
+            ic: vk.gamma_abc_g1.iter().map(compress).collect(),
+        }
+    }
+}
+
+impl From<&Proof<Bls12_381>> for Groth16Proof {
+    fn from(proof: &Proof<Bls12_381>) -> Self {
+        Self {
+            a: compress(&proof.a),
+            b: compress(&proof.b),
+            c: compress(&proof.c),
+        }
+    }
+}
+
+impl ArkworksGroth16Proof {
+    /// Assembles the guest input of an arkworks proof, its verifying key and public inputs.
+    pub fn new(
+        vk: &VerifyingKey<Bls12_381>,
+        proof: &Proof<Bls12_381>,
+        public_inputs: &[Fr],
+    ) -> Self {
+        Self {
+            vk: vk.into(),
+            proof: proof.into(),
+            public_inputs_serialized: public_inputs
+                .iter()
+                .map(|x| x.into_bigint().to_bytes_be())
+                .collect(),
+        }
+    }
+}
+
+fn bn254_g1(point: &ark_bn254::G1Affine) -> [u8; 64] {
+    let mut buffer = [0u8; 64];
+    // the point at infinity is encoded as zeroes
+    if !point.infinity {
+        buffer[..32].copy_from_slice(&point.x.into_bigint().to_bytes_be());
+        buffer[32..].copy_from_slice(&point.y.into_bigint().to_bytes_be());
+    }
+    buffer
+}
+
+fn bn254_g2(point: &ark_bn254::G2Affine) -> [u8; 128] {
+    let mut buffer = [0u8; 128];
+    if !point.infinity {
+        let coordinates = [point.x.c1, point.x.c0, point.y.c1, point.y.c0];
+        for (i, coordinate) in coordinates.iter().enumerate() {
+            buffer[32 * i..32 * (i + 1)].copy_from_slice(&coordinate.into_bigint().to_bytes_be());
+        }
+    }
+    buffer
+}
+
+impl From<&VerifyingKey<Bn254>> for Bn254Groth16VerifyingKey {
+    fn from(vk: &VerifyingKey<Bn254>) -> Self {
+        Self {
+            alpha_g1: bn254_g1(&vk.alpha_g1),
+            beta_g2: bn254_g2(&vk.beta_g2),
+            gamma_g2: bn254_g2(&vk.gamma_g2),
+            delta_g2: bn254_g2(&vk.delta_g2),
+            ic: vk.gamma_abc_g1.iter().map(bn254_g1).collect(),
+        }
+    }
+}
+
+impl From<&Proof<Bn254>> for Bn254Groth16Proof {
+    fn from(proof: &Proof<Bn254>) -> Self {
+        Self {
+            a: bn254_g1(&proof.a),
+            b: bn254_g2(&proof.b),
+            c: bn254_g1(&proof.c),
+        }
+    }
+}
+
+impl Bn254ArkworksGroth16Proof {
+    /// Assembles the guest input of a BN254 arkworks proof, its verifying key and public inputs.
+    pub fn new(
+        vk: &VerifyingKey<Bn254>,
+        proof: &Proof<Bn254>,
+        public_inputs: &[ark_bn254::Fr],
+    ) -> Self {
+        Self {
+            vk: vk.into(),
+            proof: proof.into(),
+            public_inputs_serialized: public_inputs
+                .iter()
+                .map(|x| x.into_bigint().to_bytes_be())
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,589 @@
+//! Imports of Groth16 proofs exported as JSON by snarkjs (circom) and gnark.
+//!
+//! The points are re-encoded into the guest encodings: compressed points for BLS12-381 and the
+//! Ethereum precompile encoding for BN254. Only the coordinates are range checked here; the
+//! verifier checks the proof points, and the verifying key is trusted like any other.
+
+use std::fmt;
+
+use num_bigint::BigUint;
+use serde::Deserialize;
+use serde::de::IgnoredAny;
+
+use crate::{
+    ArkworksGroth16Proof, Bn254ArkworksGroth16Proof, Bn254Groth16Proof, Bn254Groth16VerifyingKey,
+    Groth16Proof, Groth16VerifyingKey,
+};
+
+const BN254_BASE_FIELD_MODULUS: &str =
+    "21888242871839275222246405745257275088696311157297823662689037894645226208583";
+
+const BLS12_381_BASE_FIELD_MODULUS: &str = "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559787";
+
+/// A Groth16 proof imported from JSON, on the curve of its verifying key.
+pub enum ImportedGroth16Proof {
+    Bls12_381(ArkworksGroth16Proof),
+    Bn254(Bn254ArkworksGroth16Proof),
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    Json(serde_json::Error),
+    UnsupportedProtocol {
+        protocol: String,
+    },
+    UnsupportedCurve {
+        curve: String,
+    },
+    /// The verifying key isn't on BN254 nor on BLS12-381.
+    UnknownCurve,
+    CurveMismatch {
+        vk: String,
+        proof: String,
+    },
+    InvalidNumber {
+        value: String,
+    },
+    InvalidPoint {
+        point: String,
+    },
+    WrongNumberOfIcPoints {
+        expected: usize,
+        found: usize,
+    },
+    WrongNumberOfPublicInputs {
+        expected: usize,
+        found: usize,
+    },
+    /// The circuit uses gnark commitments, which the verifier doesn't support.
+    UnsupportedCommitments,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(error) => write!(f, "invalid JSON: {error}"),
+            Self::UnsupportedProtocol { protocol } => {
+                write!(f, "unsupported protocol {protocol}, expected groth16")
+            }
+            Self::UnsupportedCurve { curve } => write!(f, "unsupported curve {curve}"),
+            Self::UnknownCurve => write!(f, "the verifying key isn't on BN254 nor BLS12-381"),
+            Self::CurveMismatch { vk, proof } => {
+                write!(
+                    f,
+                    "the verifying key is on {vk} but the proof is on {proof}"
+                )
+            }
+            Self::InvalidNumber { value } => write!(f, "invalid number {value}"),
+            Self::InvalidPoint { point } => write!(f, "invalid point {point}"),
+            Self::WrongNumberOfIcPoints { expected, found } => {
+                write!(f, "expected {expected} IC points, found {found}")
+            }
+            Self::WrongNumberOfPublicInputs { expected, found } => {
+                write!(f, "expected {expected} public inputs, found {found}")
+            }
+            Self::UnsupportedCommitments => write!(f, "gnark commitments aren't supported"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<serde_json::Error> for ImportError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+/// Imports a snarkjs Groth16 proof from the contents of `verification_key.json`, `proof.json`
+/// and `public.json`.
+///
+/// The curve is read from the verifying key, `bn128` or `bls12381`. The number of public inputs
+/// must match `nPublic` and the IC points.
+pub fn import_snarkjs(
+    verification_key: &str,
+    proof: &str,
+    public: &str,
+) -> Result<ImportedGroth16Proof, ImportError> {
+    let vk: SnarkjsVerifyingKey = serde_json::from_str(verification_key)?;
+    let proof: SnarkjsProof = serde_json::from_str(proof)?;
+    let public: Vec<String> = serde_json::from_str(public)?;
+
+    for protocol in [Some(&vk.protocol), proof.protocol.as_ref()]
+        .into_iter()
+        .flatten()
+    {
+        if protocol != "groth16" {
+            return Err(ImportError::UnsupportedProtocol {
+                protocol: protocol.clone(),
+            });
+        }
+    }
+
+    let curve = Curve::from_snarkjs(&vk.curve)?;
+    match proof.curve {
+        Some(proof_curve) if Curve::from_snarkjs(&proof_curve)? != curve => {
+            return Err(ImportError::CurveMismatch {
+                vk: vk.curve,
+                proof: proof_curve,
+            });
+        }
+        _ => {}
+    }
+    if vk.ic.len() != vk.n_public + 1 {
+        return Err(ImportError::WrongNumberOfIcPoints {
+            expected: vk.n_public + 1,
+            found: vk.ic.len(),
+        });
+    }
+
+    let modulus = curve.modulus();
+    let ic = vk
+        .ic
+        .iter()
+        .enumerate()
+        .map(|(index, point)| snarkjs_g1(point, &modulus, &format!("IC[{index}]")))
+        .collect::<Result<_, _>>()?;
+
+    ParsedGroth16 {
+        curve,
+        alpha: snarkjs_g1(&vk.vk_alpha_1, &modulus, "vk_alpha_1")?,
+        beta: snarkjs_g2(&vk.vk_beta_2, &modulus, "vk_beta_2")?,
+        gamma: snarkjs_g2(&vk.vk_gamma_2, &modulus, "vk_gamma_2")?,
+        delta: snarkjs_g2(&vk.vk_delta_2, &modulus, "vk_delta_2")?,
+        ic,
+        a: snarkjs_g1(&proof.pi_a, &modulus, "pi_a")?,
+        b: snarkjs_g2(&proof.pi_b, &modulus, "pi_b")?,
+        c: snarkjs_g1(&proof.pi_c, &modulus, "pi_c")?,
+        public_inputs: public
+            .iter()
+            .map(|x| parse_number(x))
+            .collect::<Result<_, _>>()?,
+    }
+    .assemble()
+}
+
+/// Imports a gnark Groth16 proof from the JSON encodings of its `VerifyingKey` and `Proof`, and
+/// the public inputs as a JSON array in the order of the public witness.
+///
+/// gnark doesn't record the curve, so it's detected from the curve equation `alpha` satisfies.
+/// Circuits using commitments are rejected.
+pub fn import_gnark(
+    verifying_key: &str,
+    proof: &str,
+    public: &str,
+) -> Result<ImportedGroth16Proof, ImportError> {
+    let vk: GnarkVerifyingKey = serde_json::from_str(verifying_key)?;
+    let proof: GnarkProof = serde_json::from_str(proof)?;
+    let public: Vec<GnarkNumber> = serde_json::from_str(public)?;
+
+    if !vk.commitment_keys.is_empty() || !proof.commitments.is_empty() {
+        return Err(ImportError::UnsupportedCommitments);
+    }
+
+    let curve = Curve::detect(&vk.g1.alpha.x.parse()?, &vk.g1.alpha.y.parse()?)?;
+    let modulus = curve.modulus();
+    let ic = vk
+        .g1
+        .k
+        .iter()
+        .enumerate()
+        .map(|(index, point)| gnark_g1(point, &modulus, &format!("K[{index}]")))
+        .collect::<Result<_, _>>()?;
+
+    ParsedGroth16 {
+        curve,
+        alpha: gnark_g1(&vk.g1.alpha, &modulus, "Alpha")?,
+        beta: gnark_g2(&vk.g2.beta, &modulus, "Beta")?,
+        gamma: gnark_g2(&vk.g2.gamma, &modulus, "Gamma")?,
+        delta: gnark_g2(&vk.g2.delta, &modulus, "Delta")?,
+        ic,
+        a: gnark_g1(&proof.ar, &modulus, "Ar")?,
+        b: gnark_g2(&proof.bs, &modulus, "Bs")?,
+        c: gnark_g1(&proof.krs, &modulus, "Krs")?,
+        public_inputs: public
+            .iter()
+            .map(GnarkNumber::parse)
+            .collect::<Result<_, _>>()?,
+    }
+    .assemble()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Curve {
+    Bn254,
+    Bls12_381,
+}
+
+impl Curve {
+    fn from_snarkjs(curve: &str) -> Result<Self, ImportError> {
+        match curve {
+            "bn128" | "bn254" => Ok(Self::Bn254),
+            "bls12381" => Ok(Self::Bls12_381),
+            _ => Err(ImportError::UnsupportedCurve {
+                curve: curve.to_string(),
+            }),
+        }
+    }
+
+    /// Returns the curve `y^2 = x^3 + b` the point is on.
+    fn detect(x: &BigUint, y: &BigUint) -> Result<Self, ImportError> {
+        [Self::Bn254, Self::Bls12_381]
+            .into_iter()
+            .find(|curve| {
+                let p = curve.modulus();
+                x < &p && y < &p && (y * y) % &p == (x * x * x + curve.b()) % &p
+            })
+            .ok_or(ImportError::UnknownCurve)
+    }
+
+    fn modulus(self) -> BigUint {
+        let modulus = match self {
+            Self::Bn254 => BN254_BASE_FIELD_MODULUS,
+            Self::Bls12_381 => BLS12_381_BASE_FIELD_MODULUS,
+        };
+        BigUint::parse_bytes(modulus.as_bytes(), 10).unwrap()
+    }
+
+    fn b(self) -> u32 {
+        match self {
+            Self::Bn254 => 3,
+            Self::Bls12_381 => 4,
+        }
+    }
+}
+
+/// An affine G1 point, `None` at infinity.
+type G1 = Option<(BigUint, BigUint)>;
+
+/// An affine G2 point with the `(c0, c1)` coordinates of each `Fp2` element, `None` at infinity.
+type G2 = Option<([BigUint; 2], [BigUint; 2])>;
+
+/// A proof with its verifying key and public inputs, in either format.
+struct ParsedGroth16 {
+    curve: Curve,
+    alpha: G1,
+    beta: G2,
+    gamma: G2,
+    delta: G2,
+    ic: Vec<G1>,
+    a: G1,
+    b: G2,
+    c: G1,
+    public_inputs: Vec<BigUint>,
+}
+
+impl ParsedGroth16 {
+    fn assemble(self) -> Result<ImportedGroth16Proof, ImportError> {
+        let expected = self.ic.len().saturating_sub(1);
+        if self.ic.is_empty() || self.public_inputs.len() != expected {
+            return Err(ImportError::WrongNumberOfPublicInputs {
+                expected,
+                found: self.public_inputs.len(),
+            });
+        }
+
+        let mut public_inputs_serialized = Vec::with_capacity(self.public_inputs.len());
+        for input in &self.public_inputs {
+            if input.bits() > 256 {
+                return Err(ImportError::InvalidNumber {
+                    value: input.to_string(),
+                });
+            }
+            let mut buffer = vec![0u8; 32];
+            write_be(&mut buffer, input);
+            public_inputs_serialized.push(buffer);
+        }
+
+        Ok(match self.curve {
+            Curve::Bn254 => ImportedGroth16Proof::Bn254(Bn254ArkworksGroth16Proof {
+                vk: Bn254Groth16VerifyingKey {
+                    alpha_g1: bn254_g1(&self.alpha),
+                    beta_g2: bn254_g2(&self.beta),
+                    gamma_g2: bn254_g2(&self.gamma),
+                    delta_g2: bn254_g2(&self.delta),
+                    ic: self.ic.iter().map(bn254_g1).collect(),
+                },
+                proof: Bn254Groth16Proof {
+                    a: bn254_g1(&self.a),
+                    b: bn254_g2(&self.b),
+                    c: bn254_g1(&self.c),
+                },
+                public_inputs_serialized,
+            }),
+            Curve::Bls12_381 => {
+                let p = self.curve.modulus();
+                ImportedGroth16Proof::Bls12_381(ArkworksGroth16Proof {
+                    vk: Groth16VerifyingKey {
+                        alpha_g1: bls12_381_g1(&self.alpha, &p),
+                        beta_g2: bls12_381_g2(&self.beta, &p),
+                        gamma_g2: bls12_381_g2(&self.gamma, &p),
+                        delta_g2: bls12_381_g2(&self.delta, &p),
+                        ic: self.ic.iter().map(|ic| bls12_381_g1(ic, &p)).collect(),
+                    },
+                    proof: Groth16Proof {
+                        a: bls12_381_g1(&self.a, &p),
+                        b: bls12_381_g2(&self.b, &p),
+                        c: bls12_381_g1(&self.c, &p),
+                    },
+                    public_inputs_serialized,
+                })
+            }
+        })
+    }
+}
+
+/// Writes the value big-endian at the end of the buffer; the value must fit.
+fn write_be(buffer: &mut [u8], value: &BigUint) {
+    let bytes = value.to_bytes_be();
+    let offset = buffer.len() - bytes.len();
+    buffer[offset..].copy_from_slice(&bytes);
+}
+
+fn bn254_g1(point: &G1) -> [u8; 64] {
+    let mut buffer = [0u8; 64];
+    // the point at infinity is encoded as zeroes
+    if let Some((x, y)) = point {
+        write_be(&mut buffer[..32], x);
+        write_be(&mut buffer[32..], y);
+    }
+    buffer
+}
+
+fn bn254_g2(point: &G2) -> [u8; 128] {
+    let mut buffer = [0u8; 128];
+    if let Some((x, y)) = point {
+        // the imaginary part comes first
+        let coordinates = [&x[1], &x[0], &y[1], &y[0]];
+        for (i, coordinate) in coordinates.into_iter().enumerate() {
+            write_be(&mut buffer[32 * i..32 * (i + 1)], coordinate);
+        }
+    }
+    buffer
+}
+
+const COMPRESSION_FLAG: u8 = 0x80;
+const INFINITY_FLAG: u8 = 0x40;
+const SIGN_FLAG: u8 = 0x20;
+
+/// Returns whether `y` is the larger of `y` and `-y`, the sign bit of the compressed encoding.
+fn is_lexicographically_largest(y: &BigUint, p: &BigUint) -> bool {
+    y > &(p - y)
+}
+
+fn bls12_381_g1(point: &G1, p: &BigUint) -> [u8; 48] {
+    let mut buffer = [0u8; 48];
+    match point {
+        None => buffer[0] = COMPRESSION_FLAG | INFINITY_FLAG,
+        Some((x, y)) => {
+            write_be(&mut buffer, x);
+            buffer[0] |= COMPRESSION_FLAG;
+            if is_lexicographically_largest(y, p) {
+                buffer[0] |= SIGN_FLAG;
+            }
+        }
+    }
+    buffer
+}
+
+fn bls12_381_g2(point: &G2, p: &BigUint) -> [u8; 96] {
+    let mut buffer = [0u8; 96];
+    match point {
+        None => buffer[0] = COMPRESSION_FLAG | INFINITY_FLAG,
+        Some((x, y)) => {
+            // the imaginary part comes first, and decides the sign unless it's zero
+            write_be(&mut buffer[..48], &x[1]);
+            write_be(&mut buffer[48..], &x[0]);
+            buffer[0] |= COMPRESSION_FLAG;
+            let sign = if y[1] == BigUint::ZERO { &y[0] } else { &y[1] };
+            if is_lexicographically_largest(sign, p) {
+                buffer[0] |= SIGN_FLAG;
+            }
+        }
+    }
+    buffer
+}
+
+fn parse_number(value: &str) -> Result<BigUint, ImportError> {
+    BigUint::parse_bytes(value.as_bytes(), 10).ok_or_else(|| ImportError::InvalidNumber {
+        value: value.to_string(),
+    })
+}
+
+/// Parses a coordinate, checking it's in the base field.
+fn coordinate(value: BigUint, p: &BigUint, point: &str) -> Result<BigUint, ImportError> {
+    if &value >= p {
+        return Err(ImportError::InvalidPoint {
+            point: point.to_string(),
+        });
+    }
+    Ok(value)
+}
+
+#[derive(Deserialize)]
+struct SnarkjsVerifyingKey {
+    protocol: String,
+    curve: String,
+    #[serde(rename = "nPublic")]
+    n_public: usize,
+    vk_alpha_1: Vec<String>,
+    vk_beta_2: Vec<Vec<String>>,
+    vk_gamma_2: Vec<Vec<String>>,
+    vk_delta_2: Vec<Vec<String>>,
+    #[serde(rename = "IC")]
+    ic: Vec<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct SnarkjsProof {
+    pi_a: Vec<String>,
+    pi_b: Vec<Vec<String>>,
+    pi_c: Vec<String>,
+    protocol: Option<String>,
+    curve: Option<String>,
+}
+
+/// Parses a snarkjs G1 point, projective `[x, y, z]` with `z` 1, or 0 at infinity.
+fn snarkjs_g1(coordinates: &[String], p: &BigUint, point: &str) -> Result<G1, ImportError> {
+    let invalid = || ImportError::InvalidPoint {
+        point: point.to_string(),
+    };
+    let [x, y, z] = coordinates else {
+        return Err(invalid());
+    };
+    let z = parse_number(z)?;
+    if z == BigUint::ZERO {
+        return Ok(None);
+    }
+    if z != BigUint::from(1u32) {
+        return Err(invalid());
+    }
+    Ok(Some((
+        coordinate(parse_number(x)?, p, point)?,
+        coordinate(parse_number(y)?, p, point)?,
+    )))
+}
+
+/// Parses a snarkjs G2 point, `[[x0, x1], [y0, y1], [z0, z1]]` with `z` 1, or 0 at infinity.
+fn snarkjs_g2(coordinates: &[Vec<String>], p: &BigUint, point: &str) -> Result<G2, ImportError> {
+    let invalid = || ImportError::InvalidPoint {
+        point: point.to_string(),
+    };
+    let [x, y, z] = coordinates else {
+        return Err(invalid());
+    };
+    let fp2 = |c: &[String]| -> Result<[BigUint; 2], ImportError> {
+        let [c0, c1] = c else {
+            return Err(invalid());
+        };
+        Ok([
+            coordinate(parse_number(c0)?, p, point)?,
+            coordinate(parse_number(c1)?, p, point)?,
+        ])
+    };
+    let z = fp2(z)?;
+    if z[1] != BigUint::ZERO || z[0] > BigUint::from(1u32) {
+        return Err(invalid());
+    }
+    if z[0] == BigUint::ZERO {
+        return Ok(None);
+    }
+    Ok(Some((fp2(x)?, fp2(y)?)))
+}
+
+/// A field element of gnark, a JSON number when it's small and a decimal string otherwise.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GnarkNumber {
+    Integer(u64),
+    Decimal(String),
+}
+
+impl GnarkNumber {
+    fn parse(&self) -> Result<BigUint, ImportError> {
+        match self {
+            Self::Integer(value) => Ok(BigUint::from(*value)),
+            Self::Decimal(value) => parse_number(value),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GnarkG1 {
+    x: GnarkNumber,
+    y: GnarkNumber,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GnarkE2 {
+    a0: GnarkNumber,
+    a1: GnarkNumber,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GnarkG2 {
+    x: GnarkE2,
+    y: GnarkE2,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GnarkVerifyingKey {
+    g1: GnarkVerifyingKeyG1,
+    g2: GnarkVerifyingKeyG2,
+    #[serde(default)]
+    commitment_keys: Vec<IgnoredAny>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GnarkVerifyingKeyG1 {
+    alpha: GnarkG1,
+    k: Vec<GnarkG1>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GnarkVerifyingKeyG2 {
+    beta: GnarkG2,
+    gamma: GnarkG2,
+    delta: GnarkG2,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GnarkProof {
+    ar: GnarkG1,
+    bs: GnarkG2,
+    krs: GnarkG1,
+    #[serde(default)]
+    commitments: Vec<IgnoredAny>,
+}
+
+/// Parses a gnark G1 point, `(0, 0)` at infinity.
+fn gnark_g1(point: &GnarkG1, p: &BigUint, name: &str) -> Result<G1, ImportError> {
+    let x = coordinate(point.x.parse()?, p, name)?;
+    let y = coordinate(point.y.parse()?, p, name)?;
+    if x == BigUint::ZERO && y == BigUint::ZERO {
+        return Ok(None);
+    }
+    Ok(Some((x, y)))
+}
+
+/// Parses a gnark G2 point, `(0, 0)` at infinity.
+fn gnark_g2(point: &GnarkG2, p: &BigUint, name: &str) -> Result<G2, ImportError> {
+    let fp2 = |e: &GnarkE2| -> Result<[BigUint; 2], ImportError> {
+        Ok([
+            coordinate(e.a0.parse()?, p, name)?,
+            coordinate(e.a1.parse()?, p, name)?,
+        ])
+    };
+    let (x, y) = (fp2(&point.x)?, fp2(&point.y)?);
+    if x.iter().chain(&y).all(|c| *c == BigUint::ZERO) {
+        return Ok(None);
+    }
+    Ok(Some((x, y)))
+}
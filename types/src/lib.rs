@@ -1,5 +1,12 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "arkworks")]
+mod arkworks;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod merkle;
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct Sp1Groth16Proof {
@@ -11,30 +18,463 @@ pub struct Sp1Groth16Proof {
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct Sp1Groth16ProofBatch {
     pub proofs: Vec<Sp1Groth16Proof>,
+    /// The verifying key hashes of the programs the recursive program accepts proofs of.
+    pub allowed_vk_hashes: Vec<String>,
+}
+
+impl Sp1Groth16ProofBatch {
+    /// Creates an empty batch accepting the proofs of the listed programs.
+    pub fn new(allowed_vk_hashes: Vec<String>) -> Self {
+        Self {
+            proofs: Vec::new(),
+            allowed_vk_hashes,
+        }
+    }
+
+    /// Returns the Merkle proof of the [proof_leaf] of the proof at `index`, to check against a
+    /// [RecursiveOutput] with [RecursiveOutput::includes].
+    pub fn inclusion_proof(&self, index: usize) -> Vec<[u8; 32]> {
+        let leaves: Vec<_> = self
+            .proofs
+            .iter()
+            .map(|proof| proof_leaf(&proof.vk_hash, &proof.public_values))
+            .collect();
+        merkle::merkle_proof(&leaves, index)
+    }
+}
+
+/// An SP1 compressed proof, verified in the guest with SP1's recursion.
+///
+/// The proof itself isn't part of the batch: the host writes it to the stdin with
+/// `SP1Stdin::write_proof`, in batch order.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Sp1CompressedProof {
+    /// The digest of the program verifying key, as returned by `HashableKey::hash_u32`.
+    pub vk_digest: [u32; 8],
+    pub public_values: Vec<u8>,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
+pub struct Sp1CompressedProofBatch {
+    pub proofs: Vec<Sp1CompressedProof>,
+    /// The verifying key digests of the programs the recursive program accepts proofs of.
+    pub allowed_vk_digests: Vec<[u32; 8]>,
+}
+
+impl Sp1CompressedProofBatch {
+    /// Creates an empty batch accepting the proofs of the listed programs.
+    pub fn new(allowed_vk_digests: Vec<[u32; 8]>) -> Self {
+        Self {
+            proofs: Vec::new(),
+            allowed_vk_digests,
+        }
+    }
+
+    /// Returns the Merkle proof of the [proof_leaf] of the proof at `index`, to check against a
+    /// [RecursiveOutput] with [RecursiveOutput::includes_compressed].
+    pub fn inclusion_proof(&self, index: usize) -> Vec<[u8; 32]> {
+        let leaves: Vec<_> = self
+            .proofs
+            .iter()
+            .map(|proof| proof_leaf(&vk_digest_hex(&proof.vk_digest), &proof.public_values))
+            .collect();
+        merkle::merkle_proof(&leaves, index)
+    }
+}
+
+/// Returns the hex encoding of a verifying key digest, its identifier in a [RecursiveOutput].
+pub fn vk_digest_hex(vk_digest: &[u32; 8]) -> String {
+    let words: String = vk_digest.iter().map(|word| format!("{word:08x}")).collect();
+    format!("0x{words}")
+}
+
+/// Returns the leaf of an inner proof in the tree of [RecursiveOutput::public_values_root]: the
+/// verifying key hash, prefixed by its length as a little-endian `u64`, followed by the public
+/// values.
+///
+/// The key is part of the leaf, so the public values of a program can't pass for the output of
+/// another program of the batch.
+pub fn proof_leaf(vk_hash: &str, public_values: &[u8]) -> Vec<u8> {
+    let mut leaf = Vec::with_capacity(8 + vk_hash.len() + public_values.len());
+    leaf.extend_from_slice(&(vk_hash.len() as u64).to_le_bytes());
+    leaf.extend_from_slice(vk_hash.as_bytes());
+    leaf.extend_from_slice(public_values);
+    leaf
+}
+
+/// The public values committed by the recursive programs, binding the aggregate proof to the
+/// verified programs and their outputs.
+///
+/// The programs are identified by the `vk_hash` of their Groth16 proofs, or by the
+/// [vk_digest_hex] of their compressed proofs.
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Eq)]
+pub struct RecursiveOutput {
+    /// The distinct verifying key hashes of the inner proofs, in order of first appearance.
+    pub vk_hashes: Vec<String>,
+    /// The root of the [merkle] tree over the allow-list of verifying key hashes, in order.
+    pub allowed_vk_hashes_root: [u8; 32],
+    /// The root of the [merkle] tree over the [proof_leaf] of every inner proof, in batch order.
+    pub public_values_root: [u8; 32],
+    /// The number of inner proofs, the leaf count of the tree.
+    pub proof_count: u64,
+}
+
+impl RecursiveOutput {
+    /// Decodes the public values committed by the guest.
+    pub fn decode(public_values: &[u8]) -> borsh::io::Result<Self> {
+        borsh::from_slice(public_values)
+    }
+
+    /// Returns the output the guest commits for the batch once every proof verifies.
+    pub fn for_batch(batch: &Sp1Groth16ProofBatch) -> Self {
+        Self::new(
+            batch
+                .proofs
+                .iter()
+                .map(|proof| (proof.vk_hash.clone(), proof.public_values.as_slice())),
+            &batch.allowed_vk_hashes,
+        )
+    }
+
+    /// Returns the output the compressed guest commits for the batch once every proof verifies.
+    pub fn for_compressed_batch(batch: &Sp1CompressedProofBatch) -> Self {
+        let allowed_vk_hashes: Vec<_> =
+            batch.allowed_vk_digests.iter().map(vk_digest_hex).collect();
+        Self::new(
+            batch.proofs.iter().map(|proof| {
+                (
+                    vk_digest_hex(&proof.vk_digest),
+                    proof.public_values.as_slice(),
+                )
+            }),
+            &allowed_vk_hashes,
+        )
+    }
+
+    fn new<'a>(
+        proofs: impl IntoIterator<Item = (String, &'a [u8])>,
+        allowed_vk_hashes: &[String],
+    ) -> Self {
+        let mut vk_hashes = Vec::new();
+        let mut leaves = Vec::new();
+        for (vk_hash, values) in proofs {
+            leaves.push(proof_leaf(&vk_hash, values));
+            if !vk_hashes.contains(&vk_hash) {
+                vk_hashes.push(vk_hash);
+            }
+        }
+        Self {
+            vk_hashes,
+            allowed_vk_hashes_root: merkle::merkle_root(allowed_vk_hashes),
+            public_values_root: merkle::merkle_root(&leaves),
+            proof_count: leaves.len() as u64,
+        }
+    }
+
+    /// Checks that the inner proof was verified at `index` of the batch, given the Merkle proof
+    /// of its leaf from [Sp1Groth16ProofBatch::inclusion_proof].
+    pub fn includes(&self, proof: &Sp1Groth16Proof, index: usize, siblings: &[[u8; 32]]) -> bool {
+        self.includes_public_values(&proof.vk_hash, &proof.public_values, index, siblings)
+    }
+
+    /// Checks that the compressed inner proof was verified at `index` of the batch, given the
+    /// Merkle proof of its leaf from [Sp1CompressedProofBatch::inclusion_proof].
+    pub fn includes_compressed(
+        &self,
+        proof: &Sp1CompressedProof,
+        index: usize,
+        siblings: &[[u8; 32]],
+    ) -> bool {
+        let vk_hash = vk_digest_hex(&proof.vk_digest);
+        self.includes_public_values(&vk_hash, &proof.public_values, index, siblings)
+    }
+
+    fn includes_public_values(
+        &self,
+        vk_hash: &str,
+        public_values: &[u8],
+        index: usize,
+        siblings: &[[u8; 32]],
+    ) -> bool {
+        merkle::verify_merkle_proof(
+            &self.public_values_root,
+            self.proof_count as usize,
+            index,
+            &proof_leaf(vk_hash, public_values),
+            siblings,
+        )
+    }
+}
+
+/// A batch of Groth16 proofs, with every verifying key listed once and referenced by index.
+#[derive(BorshSerialize, BorshDeserialize, Default)]
 pub struct ArkworksGroth16ProofBatch {
-    pub proofs: Vec<ArkworksGroth16Proof>,
+    pub vks: Vec<Groth16VerifyingKey>,
+    pub proofs: Vec<Groth16BatchProof>,
+    pub bn254_vks: Vec<Bn254Groth16VerifyingKey>,
+    pub bn254_proofs: Vec<Bn254Groth16BatchProof>,
+}
+
+impl ArkworksGroth16ProofBatch {
+    /// Adds a BLS12-381 proof, listing its verifying key unless the batch already has it.
+    pub fn push(&mut self, proof: ArkworksGroth16Proof) {
+        self.proofs.push(Groth16BatchProof {
+            vk_index: vk_index(&mut self.vks, proof.vk),
+            proof: proof.proof,
+            public_inputs_serialized: proof.public_inputs_serialized,
+        });
+    }
+
+    /// Adds a BN254 proof, listing its verifying key unless the batch already has it.
+    pub fn push_bn254(&mut self, proof: Bn254ArkworksGroth16Proof) {
+        self.bn254_proofs.push(Bn254Groth16BatchProof {
+            vk_index: vk_index(&mut self.bn254_vks, proof.vk),
+            proof: proof.proof,
+            public_inputs_serialized: proof.public_inputs_serialized,
+        });
+    }
+}
+
+fn vk_index<K: PartialEq>(vks: &mut Vec<K>, vk: K) -> u32 {
+    let index = match vks.iter().position(|k| *k == vk) {
+        Some(index) => index,
+        None => {
+            vks.push(vk);
+            vks.len() - 1
+        }
+    };
+    index as u32
+}
+
+fn deserialize_public_inputs(public_inputs_serialized: &[Vec<u8>]) -> Vec<BigUint> {
+    public_inputs_serialized
+        .iter()
+        .map(|x| BigUint::from_bytes_be(x))
+        .collect()
+}
+
+/// A BLS12-381 proof of a batch, with the index of its verifying key in the batch.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Groth16BatchProof {
+    pub vk_index: u32,
+    pub proof: Groth16Proof,
+    pub public_inputs_serialized: Vec<Vec<u8>>,
+}
+
+impl Groth16BatchProof {
+    pub fn deserialize_public_inputs(&self) -> Vec<BigUint> {
+        deserialize_public_inputs(&self.public_inputs_serialized)
+    }
+}
+
+/// A BN254 proof of a batch, with the index of its verifying key in the batch.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Bn254Groth16BatchProof {
+    pub vk_index: u32,
+    pub proof: Bn254Groth16Proof,
+    pub public_inputs_serialized: Vec<Vec<u8>>,
+}
+
+impl Bn254Groth16BatchProof {
+    pub fn deserialize_public_inputs(&self) -> Vec<BigUint> {
+        deserialize_public_inputs(&self.public_inputs_serialized)
+    }
+}
+
+/// A BLS12-381 Groth16 verifying key, as compressed points.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Groth16VerifyingKey {
+    pub alpha_g1: [u8; 48],
+    pub beta_g2: [u8; 96],
+    pub gamma_g2: [u8; 96],
+    pub delta_g2: [u8; 96],
+    /// The IC points; the first one is the constant term, followed by one per public input.
+    pub ic: Vec<[u8; 48]>,
+}
+
+impl Groth16VerifyingKey {
+    /// Returns the SHA-256 hash of the key, binding the verified proofs to the circuit.
+    ///
+    /// The points are hashed in declaration order. Every point but the IC has a fixed size and
+    /// the IC points come last, so the encoding is injective.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.alpha_g1);
+        hasher.update(self.beta_g2);
+        hasher.update(self.gamma_g2);
+        hasher.update(self.delta_g2);
+        for ic in &self.ic {
+            hasher.update(ic);
+        }
+        hasher.finalize().into()
+    }
+}
+
+/// A BLS12-381 Groth16 proof, as compressed points.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Groth16Proof {
+    pub a: [u8; 48],
+    pub b: [u8; 96],
+    pub c: [u8; 48],
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct ArkworksGroth16Proof {
-    pub g1_affine_points_serialized: Vec<[u8; 48]>,
-    pub g2_affine_points_serialized: Vec<[u8; 96]>,
+    pub vk: Groth16VerifyingKey,
+    pub proof: Groth16Proof,
     pub public_inputs_serialized: Vec<Vec<u8>>,
-    pub ics_input: Vec<Vec<u8>>,
 }
 
 impl ArkworksGroth16Proof {
     pub fn deserialize_public_inputs(&self) -> Vec<BigUint> {
-        self.public_inputs_serialized
+        deserialize_public_inputs(&self.public_inputs_serialized)
+    }
+}
+
+/// A BN254 Groth16 verifying key, as uncompressed points.
+///
+/// The points use the Ethereum precompile encoding: big-endian coordinates, with the G2
+/// coordinates written imaginary part first.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Bn254Groth16VerifyingKey {
+    pub alpha_g1: [u8; 64],
+    pub beta_g2: [u8; 128],
+    pub gamma_g2: [u8; 128],
+    pub delta_g2: [u8; 128],
+    /// The IC points; the first one is the constant term, followed by one per public input.
+    pub ic: Vec<[u8; 64]>,
+}
+
+impl Bn254Groth16VerifyingKey {
+    /// Returns the SHA-256 hash of the key, binding the verified proofs to the circuit.
+    ///
+    /// The points are hashed in the same order as [Groth16VerifyingKey::hash].
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.alpha_g1);
+        hasher.update(self.beta_g2);
+        hasher.update(self.gamma_g2);
+        hasher.update(self.delta_g2);
+        for ic in &self.ic {
+            hasher.update(ic);
+        }
+        hasher.finalize().into()
+    }
+}
+
+/// A BN254 Groth16 proof, as uncompressed points in the encoding of [Bn254Groth16VerifyingKey].
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Bn254Groth16Proof {
+    pub a: [u8; 64],
+    pub b: [u8; 128],
+    pub c: [u8; 64],
+}
+
+/// A BN254 Groth16 proof with its verifying key and public inputs, from arkworks, gnark or circom.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Bn254ArkworksGroth16Proof {
+    pub vk: Bn254Groth16VerifyingKey,
+    pub proof: Bn254Groth16Proof,
+    pub public_inputs_serialized: Vec<Vec<u8>>,
+}
+
+impl Bn254ArkworksGroth16Proof {
+    pub fn deserialize_public_inputs(&self) -> Vec<BigUint> {
+        deserialize_public_inputs(&self.public_inputs_serialized)
+    }
+}
+
+/// The public values committed by the recursive arkworks program, binding the aggregate proof to
+/// the verified statements.
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Eq)]
+pub struct ArkworksGroth16Output {
+    /// The hash of the verifying key of each BLS12-381 proof, in batch order.
+    pub vk_hashes: Vec<[u8; 32]>,
+    /// The hash of the verifying key of each BN254 proof, in batch order.
+    pub bn254_vk_hashes: Vec<[u8; 32]>,
+    /// The hash of the public inputs of every proof, as computed by [hash_public_inputs].
+    pub public_inputs_hash: [u8; 32],
+    /// The number of verified proofs, BLS12-381 and BN254.
+    pub proof_count: u64,
+}
+
+impl ArkworksGroth16Output {
+    /// Decodes the public values committed by the guest.
+    pub fn decode(public_values: &[u8]) -> borsh::io::Result<Self> {
+        borsh::from_slice(public_values)
+    }
+
+    /// Returns the output the guest commits for the batch once every proof verifies.
+    ///
+    /// The BLS12-381 proofs come first, then the BN254 ones.
+    pub fn for_batch(batch: &ArkworksGroth16ProofBatch) -> Self {
+        let public_inputs: Vec<_> = batch
+            .proofs
             .iter()
-            .map(|x| BigUint::from_bytes_be(x))
-            .collect()
+            .map(|proof| proof.deserialize_public_inputs())
+            .chain(
+                batch
+                    .bn254_proofs
+                    .iter()
+                    .map(|proof| proof.deserialize_public_inputs()),
+            )
+            .collect();
+        Self {
+            vk_hashes: batch
+                .proofs
+                .iter()
+                .map(|proof| batch.vks[proof.vk_index as usize].hash())
+                .collect(),
+            bn254_vk_hashes: batch
+                .bn254_proofs
+                .iter()
+                .map(|proof| batch.bn254_vks[proof.vk_index as usize].hash())
+                .collect(),
+            public_inputs_hash: hash_public_inputs(public_inputs.iter().map(Vec::as_slice)),
+            proof_count: public_inputs.len() as u64,
+        }
     }
 }
 
+/// Returns the SHA-256 hash of the public inputs of a sequence of proofs.
+///
+/// Each proof contributes its number of inputs as a little-endian `u64`, followed by its inputs as
+/// 32-byte big-endian words, so the proofs can't be split differently for the same hash.
+///
+/// # Panics
+///
+/// Panics if an input doesn't fit in 32 bytes; the verifiers reject such inputs.
+pub fn hash_public_inputs<'a>(proofs: impl IntoIterator<Item = &'a [BigUint]>) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for inputs in proofs {
+        hasher.update((inputs.len() as u64).to_le_bytes());
+        for input in inputs {
+            let bytes = input.to_bytes_be();
+            assert!(bytes.len() <= 32, "public input larger than 32 bytes");
+            let mut word = [0u8; 32];
+            word[32 - bytes.len()..].copy_from_slice(&bytes);
+            hasher.update(word);
+        }
+    }
+    hasher.finalize().into()
+}
+
+/// Groth16 proofs verified one by one in the zkVM, to compare the verdicts with the host.
+#[derive(BorshSerialize, BorshDeserialize, Default)]
+pub struct Groth16ParityCases {
+    pub proofs: Vec<ArkworksGroth16Proof>,
+    pub bn254_proofs: Vec<Bn254ArkworksGroth16Proof>,
+}
+
+/// Whether each proof of [Groth16ParityCases] was accepted, in order.
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Eq)]
+pub struct Groth16ParityOutput {
+    pub verdicts: Vec<bool>,
+    /// The verdicts of the BLS12-381 proofs verified as batches of one proof.
+    pub batch_verdicts: Vec<bool>,
+    pub bn254_verdicts: Vec<bool>,
+}
+
 pub type MockNode = Vec<u8>;
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct MockMerkleProofBatch {
@@ -52,3 +492,95 @@ pub struct SmtOpeningInput {
 pub struct SmtOpeningBatch {
     pub proofs: Vec<SmtOpeningInput>,
 }
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Eq)]
+pub struct SmtOpeningOutput {
+    pub roots: Vec<[u8; 32]>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub enum SmtOperation {
+    Get { context: String, data: Vec<u8> },
+    Insert { context: String, data: Vec<u8> },
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SmtUpdateInput {
+    pub witness_serialized: Vec<u8>,
+    pub root: [u8; 32],
+    pub operations: Vec<SmtOperation>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Eq)]
+pub struct SmtUpdateOutput {
+    pub root: [u8; 32],
+    pub updated_root: [u8; 32],
+    /// The [hash_smt_operations] of the operations that turned `root` into `updated_root`.
+    pub operations_hash: [u8; 32],
+}
+
+/// Returns the SHA-256 hash of the borsh encoding of the operations.
+pub fn hash_smt_operations(operations: &[SmtOperation]) -> [u8; 32] {
+    Sha256::digest(borsh::to_vec(operations).unwrap()).into()
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Eq)]
+pub struct HasherVectorsOutput {
+    pub checked: u64,
+    pub skipped: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inclusion_is_bound_to_the_program() {
+        let program_x = "0x".to_string() + &"ab".repeat(32);
+        let program_y = "0x".to_string() + &"cd".repeat(32);
+        let mut batch = Sp1Groth16ProofBatch::new(vec![program_x.clone(), program_y.clone()]);
+        for (vk_hash, public_values) in [
+            (&program_x, b"x0"),
+            (&program_y, b"y1"),
+            (&program_x, b"x2"),
+        ] {
+            batch.proofs.push(Sp1Groth16Proof {
+                proof: Vec::new(),
+                public_values: public_values.to_vec(),
+                vk_hash: vk_hash.clone(),
+            });
+        }
+        let output = RecursiveOutput::for_batch(&batch);
+        for (index, proof) in batch.proofs.iter().enumerate() {
+            assert!(output.includes(proof, index, &batch.inclusion_proof(index)));
+        }
+
+        // the output of program Y, presented as an output of program X at the same index
+        let forged = Sp1Groth16Proof {
+            proof: Vec::new(),
+            public_values: batch.proofs[1].public_values.clone(),
+            vk_hash: program_x,
+        };
+        assert!(!output.includes(&forged, 1, &batch.inclusion_proof(1)));
+
+        let digest_x = [1; 8];
+        let digest_y = [2; 8];
+        let mut batch = Sp1CompressedProofBatch::new(vec![digest_x, digest_y]);
+        for (vk_digest, public_values) in [(digest_x, b"x0"), (digest_y, b"y1")] {
+            batch.proofs.push(Sp1CompressedProof {
+                vk_digest,
+                public_values: public_values.to_vec(),
+            });
+        }
+        let output = RecursiveOutput::for_compressed_batch(&batch);
+        for (index, proof) in batch.proofs.iter().enumerate() {
+            assert!(output.includes_compressed(proof, index, &batch.inclusion_proof(index)));
+        }
+
+        let forged = Sp1CompressedProof {
+            vk_digest: digest_x,
+            ..batch.proofs[1].clone()
+        };
+        assert!(!output.includes_compressed(&forged, 1, &batch.inclusion_proof(1)));
+    }
+}
@@ -0,0 +1,134 @@
+//! A binary SHA-256 Merkle tree over byte strings, committing the public values of recursive proofs.
+//!
+//! Leaves are hashed as `sha256(0x00 || data)` and inner nodes as `sha256(0x01 || left || right)`,
+//! so a leaf can't pass for a node. A level with an odd number of nodes carries its last node up
+//! unchanged. The proofs don't record where that happens, so checking one takes the leaf count.
+
+use sha2::{Digest, Sha256};
+
+/// The root of a tree without leaves.
+pub const EMPTY_ROOT: [u8; 32] = [0; 32];
+
+pub fn leaf_hash(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0u8]);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([1u8]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [last] => *last,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Returns the root of the tree over the leaves.
+pub fn merkle_root<T: AsRef<[u8]>>(leaves: &[T]) -> [u8; 32] {
+    let mut level: Vec<_> = leaves.iter().map(|leaf| leaf_hash(leaf.as_ref())).collect();
+    if level.is_empty() {
+        return EMPTY_ROOT;
+    }
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// Returns the siblings on the path from the leaf at `index` to the root, bottom up.
+///
+/// # Panics
+///
+/// Panics if `index` is out of bounds.
+pub fn merkle_proof<T: AsRef<[u8]>>(leaves: &[T], mut index: usize) -> Vec<[u8; 32]> {
+    assert!(index < leaves.len(), "leaf index out of bounds");
+    let mut level: Vec<_> = leaves.iter().map(|leaf| leaf_hash(leaf.as_ref())).collect();
+    let mut siblings = Vec::new();
+    while level.len() > 1 {
+        // the carried up last node has no sibling
+        if let Some(sibling) = level.get(index ^ 1) {
+            siblings.push(*sibling);
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    siblings
+}
+
+/// Checks that `data` is the leaf at `index` of the tree of `leaf_count` leaves with the root.
+pub fn verify_merkle_proof(
+    root: &[u8; 32],
+    leaf_count: usize,
+    mut index: usize,
+    data: &[u8],
+    siblings: &[[u8; 32]],
+) -> bool {
+    if index >= leaf_count {
+        return false;
+    }
+    let mut siblings = siblings.iter();
+    let mut node = leaf_hash(data);
+    let mut width = leaf_count;
+    while width > 1 {
+        if index ^ 1 < width {
+            let Some(sibling) = siblings.next() else {
+                return false;
+            };
+            node = if index & 1 == 1 {
+                node_hash(sibling, &node)
+            } else {
+                node_hash(&node, sibling)
+            };
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+    siblings.next().is_none() && node == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_leaf_is_included() {
+        assert_eq!(merkle_root::<&[u8]>(&[]), EMPTY_ROOT);
+        for leaf_count in 1..=9 {
+            let leaves: Vec<Vec<u8>> = (0..leaf_count).map(|i| vec![i as u8; i + 1]).collect();
+            let root = merkle_root(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let siblings = merkle_proof(&leaves, index);
+                assert!(verify_merkle_proof(
+                    &root, leaf_count, index, leaf, &siblings
+                ));
+
+                // another leaf or position
+                assert!(!verify_merkle_proof(
+                    &root, leaf_count, index, b"other", &siblings
+                ));
+                let other_index = (index + 1) % leaf_count;
+                if leaves[other_index] != *leaf {
+                    assert!(!verify_merkle_proof(
+                        &root,
+                        leaf_count,
+                        other_index,
+                        leaf,
+                        &siblings
+                    ));
+                }
+            }
+        }
+    }
+}
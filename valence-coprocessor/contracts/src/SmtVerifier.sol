@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.20;
+
+/// @title SmtVerifier
+/// @notice Verifies openings of a Valence sparse Merkle tree computed under the keccak256
+/// execution context.
+/// @dev Generated by `valence_smt::solidity_verifier`; do not edit by hand.
+library SmtVerifier {
+    bytes1 internal constant DATA_PREFIX = 0x00;
+    bytes1 internal constant MERGE_PREFIX = 0x01;
+    bytes1 internal constant KEY_PREFIX = 0x02;
+    uint256 internal constant MAX_DEPTH = 256;
+
+    /// @notice Computes the leaf key of the data under the provided context.
+    function key(string memory context, bytes memory data) internal pure returns (bytes32) {
+        return keccak256(abi.encodePacked(KEY_PREFIX, uint64(bytes(context).length), context, data));
+    }
+
+    /// @notice Computes the leaf node of the data.
+    function hash(bytes memory data) internal pure returns (bytes32) {
+        return keccak256(abi.encodePacked(DATA_PREFIX, data));
+    }
+
+    /// @notice Computes the parent node of the provided children.
+    function merge(bytes32 left, bytes32 right) internal pure returns (bytes32) {
+        return keccak256(abi.encodePacked(MERGE_PREFIX, left, right));
+    }
+
+    /// @notice Verifies that the data opens to the root under the provided context.
+    /// @param opening The siblings from the leaf to the root, as in `SmtOpening::opening`.
+    function verify(string memory context, bytes32 root, bytes memory data, bytes32[] memory opening)
+        internal
+        pure
+        returns (bool)
+    {
+        if (opening.length > MAX_DEPTH) {
+            return false;
+        }
+
+        uint256 k = uint256(key(context, data));
+        bytes32 node = hash(data);
+        uint256 depth = opening.length;
+
+        for (uint256 i = 0; i < opening.length; i++) {
+            depth -= 1;
+
+            // the key bits select the path from the root, most significant bit first
+            if ((k >> (MAX_DEPTH - 1 - depth)) & 1 == 0) {
+                node = merge(node, opening[i]);
+            } else {
+                node = merge(opening[i], node);
+            }
+        }
+
+        return node == root;
+    }
+}
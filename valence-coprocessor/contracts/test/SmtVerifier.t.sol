@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.20;
+
+import {SmtVerifier} from "../src/SmtVerifier.sol";
+import {SmtVerifierFixture} from "./SmtVerifierFixture.sol";
+
+/// @notice Checks the generated verifier against an opening computed by `valence_smt`.
+contract SmtVerifierTest {
+    function test_VerifiesRustOpening() public pure {
+        require(
+            SmtVerifier.verify(
+                SmtVerifierFixture.CONTEXT,
+                SmtVerifierFixture.ROOT,
+                SmtVerifierFixture.data(),
+                SmtVerifierFixture.opening()
+            ),
+            "the opening doesn't verify"
+        );
+    }
+
+    function test_RejectsTamperedData() public pure {
+        bytes memory data = SmtVerifierFixture.data();
+        data[data.length - 1] = data[data.length - 1] ^ bytes1(0x01);
+
+        require(
+            !SmtVerifier.verify(
+                SmtVerifierFixture.CONTEXT, SmtVerifierFixture.ROOT, data, SmtVerifierFixture.opening()
+            ),
+            "a tampered data verifies"
+        );
+    }
+
+    function test_RejectsTamperedSibling() public pure {
+        bytes32[] memory opening = SmtVerifierFixture.opening();
+        opening[0] = opening[0] ^ bytes32(uint256(1));
+
+        require(
+            !SmtVerifier.verify(
+                SmtVerifierFixture.CONTEXT, SmtVerifierFixture.ROOT, SmtVerifierFixture.data(), opening
+            ),
+            "a tampered sibling verifies"
+        );
+    }
+
+    function test_RejectsOtherContext() public pure {
+        require(
+            !SmtVerifier.verify(
+                "other", SmtVerifierFixture.ROOT, SmtVerifierFixture.data(), SmtVerifierFixture.opening()
+            ),
+            "the opening verifies under another context"
+        );
+    }
+}
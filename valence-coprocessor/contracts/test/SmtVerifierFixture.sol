@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.20;
+
+/// @title SmtVerifierFixture
+/// @notice An opening of a sparse Merkle tree computed by `valence_smt` under the keccak256
+/// execution context.
+/// @dev Generated by the `solidity` tests of `valence_smt`; do not edit by hand.
+library SmtVerifierFixture {
+    string internal constant CONTEXT = "poem";
+    bytes32 internal constant ROOT = 0x02f82169eec36185cde9963e16700114dd3aa299d5dce17cfffb7cd94773112a;
+
+    function data() internal pure returns (bytes memory) {
+        return hex"000007";
+    }
+
+    function opening() internal pure returns (bytes32[] memory siblings) {
+        siblings = new bytes32[](5);
+        siblings[0] = 0x2f7fb0b7efb0e8eb9160534a0abe28712bfe4ca897ff044969ee032e84497085;
+        siblings[1] = 0xcc21cc9477a6aea5df8cae73422b700c47e54d892691e099167e77befc94780a;
+        siblings[2] = 0x6175509cd2d89295adaac730240b2a366297a3a9dea3a5581fc829f92953d82f;
+        siblings[3] = 0x555fb3d53ecb9bdda695b9e62a6eaa6ba731e7e9d802fc79807b15f7325ba142;
+        siblings[4] = 0x5b00fff3f89b17521d948ef1993075c0bf8ad2e524a07d77edf4f7fdc3fc9c7d;
+    }
+}
@@ -15,6 +15,8 @@ impl Blake3Hasher {
 }
 
 impl Hasher for Blake3Hasher {
+    type Output = Hash;
+
     fn key(context: &str, data: &[u8]) -> Hash {
         ::blake3::derive_key(context, data)
     }
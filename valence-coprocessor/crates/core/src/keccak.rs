@@ -0,0 +1,69 @@
+use crate::{ExecutionContext, Hash, Hasher};
+use tiny_keccak::{Hasher as _, Keccak};
+
+/// A Keccak-256 hasher implementation for the Valence protocol.
+///
+/// The digests match the EVM `keccak256` opcode, so the roots of a sparse Merkle tree computed
+/// under this context can be recomputed on-chain. Guest programs pick up the SP1 keccak precompile
+/// through the SP1 patch of the `tiny-keccak` crate.
+pub struct Keccak256Hasher;
+
+/// A Keccak-256 execution environment for the Valence protocol.
+pub struct KeccakContext;
+
+impl Keccak256Hasher {
+    /// Prefix for data hash.
+    pub const DATA_PREFIX: &[u8] = &[0x00];
+
+    /// Prefix for node hash.
+    pub const MERGE_PREFIX: &[u8] = &[0x01];
+
+    /// Prefix for key hash.
+    pub const KEY_PREFIX: &[u8] = &[0x02];
+}
+
+impl Hasher for Keccak256Hasher {
+    type Output = Hash;
+
+    fn key(context: &str, data: &[u8]) -> Hash {
+        let mut hash = Hash::default();
+        let mut hasher = Keccak::v256();
+        hasher.update(Self::KEY_PREFIX);
+
+        // the context is length-prefixed so it can't bleed into the data
+        hasher.update(&(context.len() as u64).to_be_bytes());
+        hasher.update(context.as_bytes());
+
+        hasher.update(data);
+        hasher.finalize(&mut hash);
+
+        hash
+    }
+
+    fn hash(data: &[u8]) -> Hash {
+        let mut hash = Hash::default();
+        let mut hasher = Keccak::v256();
+        hasher.update(Self::DATA_PREFIX);
+
+        hasher.update(data);
+        hasher.finalize(&mut hash);
+
+        hash
+    }
+
+    fn merge(a: &Hash, b: &Hash) -> Hash {
+        let mut hash = Hash::default();
+        let mut hasher = Keccak::v256();
+        hasher.update(Self::MERGE_PREFIX);
+
+        hasher.update(a);
+        hasher.update(b);
+        hasher.finalize(&mut hash);
+
+        hash
+    }
+}
+
+impl ExecutionContext for KeccakContext {
+    type Hasher = Keccak256Hasher;
+}
@@ -2,50 +2,127 @@
 #![doc = include_str!("../README.md")]
 #![no_std]
 
+#[cfg(feature = "poseidon")]
+extern crate alloc;
+
 #[cfg(feature = "blake3")]
 mod blake3;
 
+#[cfg(feature = "keccak")]
+mod keccak;
+
+#[cfg(feature = "poseidon")]
+mod poseidon;
+
 #[cfg(feature = "sha2")]
 mod sha2;
 
-#[cfg(feature = "sha3-sp1")]
+#[cfg(feature = "sha3")]
 mod sha3;
 
+mod vectors;
+
 #[cfg(feature = "blake3")]
 pub use blake3::*;
 
+#[cfg(feature = "keccak")]
+pub use keccak::*;
+
+#[cfg(feature = "poseidon")]
+pub use poseidon::*;
+
 #[cfg(feature = "sha2")]
 pub use sha2::*;
 
-#[cfg(feature = "sha3-sp1")]
+#[cfg(feature = "sha3")]
 pub use sha3::*;
 
-/// The hash output byte-length used in cryptographic primitives like the sparse Merkle tree.
+pub use vectors::*;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// The hash output byte-length of the default hashers.
 pub const HASH_LEN: usize = 32;
 
-/// The hash output array used in cryptographic primitives like the sparse Merkle tree.
+/// The hash output array of the default hashers.
 pub type Hash = [u8; HASH_LEN];
 
+/// The hash output of the hasher of an execution context.
+pub type HashOf<C> = <<C as ExecutionContext>::Hasher as Hasher>::Output;
+
+/// A fixed-width hash output used in cryptographic primitives like the sparse Merkle tree.
+///
+/// Implemented for byte arrays of any length, so hashers can produce 32, 48 or 64 bytes digests.
+pub trait Digest:
+    Copy
+    + Eq
+    + Ord
+    + core::hash::Hash
+    + core::fmt::Debug
+    + AsRef<[u8]>
+    + AsMut<[u8]>
+    + BorshSerialize
+    + BorshDeserialize
+    + Send
+    + Sync
+    + 'static
+{
+    /// The byte-length of the digest.
+    const LEN: usize;
+
+    /// Returns the all-zeroes digest, used as the empty node of a tree.
+    fn zero() -> Self;
+
+    /// Reads a digest from its bytes, returning `None` if the length doesn't match [Self::LEN].
+    fn from_slice(bytes: &[u8]) -> Option<Self>;
+
+    /// Returns `true` if this is the [Self::zero] digest.
+    fn is_zero(&self) -> bool {
+        self == &Self::zero()
+    }
+}
+
+impl<const N: usize> Digest for [u8; N] {
+    const LEN: usize = N;
+
+    fn zero() -> Self {
+        [0; N]
+    }
+
+    fn from_slice(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok()
+    }
+}
+
 /// The hasher high-level definition.
 pub trait Hasher {
+    /// The hash output of the implementation.
+    ///
+    /// Note: The existing hashers output a 32 bytes [Hash].
+    type Output: Digest;
+
     /// Uses the implementation of the hash function to create a key under a constant context.
     ///
     /// This is useful to emulate namespace within a cryptographic space.
-    fn key(context: &str, data: &[u8]) -> Hash;
+    fn key(context: &str, data: &[u8]) -> Self::Output;
 
     /// Hashes the data arguments into an array of bytes.
-    fn hash(data: &[u8]) -> Hash;
+    fn hash(data: &[u8]) -> Self::Output;
 
     /// Merges the two hashes into a single one, extending the cryptographic properties of the
     /// underlying hash function.
-    fn merge(a: &Hash, b: &Hash) -> Hash;
+    fn merge(a: &Self::Output, b: &Self::Output) -> Self::Output;
 }
 
 /// Execution context for guest programs of a zkVM.
 ///
-/// This trait's implementations handle the serialization of witness data into the guest program,
-/// while also preserving an execution context. They are capable of performing a dry-run to select
-/// and send relevant data to the ZK execution environment.
+/// This trait's implementations define the primitives shared by the host and the guest program,
+/// so both sides compute identical states out of the same data.
+///
+/// The host performs a dry-run of the guest program operations under the same context, capturing
+/// the relevant witness data (i.e. the `RecordingBackend` of the sparse Merkle tree). The witness
+/// is then sent to the ZK execution environment, where the operations are replayed against a
+/// backend built solely from it.
 pub trait ExecutionContext {
     /// The concrete hash implementation for the execution.
     ///
@@ -0,0 +1,162 @@
+use alloc::{boxed::Box, vec::Vec};
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::{
+    crh::{poseidon::CRH, CRHScheme},
+    sponge::poseidon::{find_poseidon_ark_and_mds, PoseidonConfig},
+};
+use ark_ff::{BigInteger, PrimeField};
+use once_cell::race::OnceBox;
+
+use crate::{ExecutionContext, Hash, Hasher};
+
+#[cfg(feature = "poseidon-r1cs")]
+mod constraints;
+
+#[cfg(feature = "poseidon-r1cs")]
+pub use constraints::*;
+
+/// A Poseidon hasher implementation for the Valence protocol, over the BLS12-381 scalar field.
+///
+/// The permutation has width 3 (rate 2, capacity 1), S-box `x^5`, 8 full rounds and 57 partial
+/// rounds, which is the 128-bit security instance of the Poseidon paper for a 255-bit field. The
+/// round constants and the MDS matrix are derived with the paper's Grain LFSR, as implemented by
+/// [find_poseidon_ark_and_mds].
+///
+/// Bytes are encoded into field elements as little-endian chunks of [Self::CHUNK_LEN] bytes, so
+/// every chunk is canonical. Each input is prefixed with its domain and its byte lengths, keeping
+/// the encoding injective. The outputs are the 32 bytes little-endian encoding of the squeezed
+/// field element.
+///
+/// Nodes are read with [PrimeField::from_le_bytes_mod_order]; the hasher only ever produces
+/// canonical encodings, so this doesn't introduce collisions between the nodes of a tree.
+pub struct PoseidonHasher;
+
+/// A Poseidon execution environment for the Valence protocol.
+pub struct PoseidonContext;
+
+impl PoseidonHasher {
+    /// Prefix for data hash.
+    pub const DATA_PREFIX: u64 = 0;
+
+    /// Prefix for node hash.
+    pub const MERGE_PREFIX: u64 = 1;
+
+    /// Prefix for key hash.
+    pub const KEY_PREFIX: u64 = 2;
+
+    /// Number of bytes packed into a single field element.
+    pub const CHUNK_LEN: usize = 31;
+
+    /// Rate of the sponge.
+    pub const RATE: usize = 2;
+
+    /// Capacity of the sponge.
+    pub const CAPACITY: usize = 1;
+
+    /// Exponent of the S-box.
+    pub const ALPHA: u64 = 5;
+
+    /// Number of full rounds of the permutation.
+    pub const FULL_ROUNDS: usize = 8;
+
+    /// Number of partial rounds of the permutation.
+    pub const PARTIAL_ROUNDS: usize = 57;
+
+    /// Returns the Poseidon parameters of the hasher.
+    ///
+    /// The parameters are derived on first use and cached for subsequent calls.
+    pub fn params() -> &'static PoseidonConfig<Fr> {
+        static PARAMS: OnceBox<PoseidonConfig<Fr>> = OnceBox::new();
+
+        PARAMS.get_or_init(|| {
+            let (ark, mds) = find_poseidon_ark_and_mds::<Fr>(
+                Fr::MODULUS_BIT_SIZE as u64,
+                Self::RATE,
+                Self::FULL_ROUNDS as u64,
+                Self::PARTIAL_ROUNDS as u64,
+                0,
+            );
+
+            Box::new(PoseidonConfig::new(
+                Self::FULL_ROUNDS,
+                Self::PARTIAL_ROUNDS,
+                Self::ALPHA,
+                mds,
+                ark,
+                Self::RATE,
+                Self::CAPACITY,
+            ))
+        })
+    }
+
+    /// Reads a hash as a field element.
+    pub fn to_field(hash: &Hash) -> Fr {
+        Fr::from_le_bytes_mod_order(hash)
+    }
+
+    /// Writes a field element as a hash.
+    pub fn from_field(element: &Fr) -> Hash {
+        let mut hash = Hash::default();
+        let bytes = element.into_bigint().to_bytes_le();
+
+        hash.copy_from_slice(&bytes);
+
+        hash
+    }
+
+    /// Packs the bytes into field elements, as little-endian chunks of [Self::CHUNK_LEN] bytes.
+    pub fn pack(bytes: &[u8]) -> impl Iterator<Item = Fr> + '_ {
+        bytes
+            .chunks(Self::CHUNK_LEN)
+            .map(Fr::from_le_bytes_mod_order)
+    }
+
+    fn evaluate(input: Vec<Fr>) -> Hash {
+        let output =
+            CRH::<Fr>::evaluate(Self::params(), input).expect("the poseidon input is never empty");
+
+        Self::from_field(&output)
+    }
+}
+
+impl Hasher for PoseidonHasher {
+    type Output = Hash;
+
+    fn key(context: &str, data: &[u8]) -> Hash {
+        let input = [
+            Fr::from(Self::KEY_PREFIX),
+            Fr::from(context.len() as u64),
+            Fr::from(data.len() as u64),
+        ]
+        .into_iter()
+        .chain(Self::pack(context.as_bytes()))
+        .chain(Self::pack(data))
+        .collect();
+
+        Self::evaluate(input)
+    }
+
+    fn hash(data: &[u8]) -> Hash {
+        let input = [Fr::from(Self::DATA_PREFIX), Fr::from(data.len() as u64)]
+            .into_iter()
+            .chain(Self::pack(data))
+            .collect();
+
+        Self::evaluate(input)
+    }
+
+    fn merge(a: &Hash, b: &Hash) -> Hash {
+        let input = [
+            Fr::from(Self::MERGE_PREFIX),
+            Self::to_field(a),
+            Self::to_field(b),
+        ]
+        .into();
+
+        Self::evaluate(input)
+    }
+}
+
+impl ExecutionContext for PoseidonContext {
+    type Hasher = PoseidonHasher;
+}
@@ -0,0 +1,239 @@
+use alloc::vec::Vec;
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::crh::{
+    poseidon::constraints::{CRHGadget, CRHParametersVar},
+    CRHSchemeGadget,
+};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    boolean::Boolean,
+    convert::{ToBitsGadget, ToConstraintFieldGadget},
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    select::CondSelectGadget,
+    uint8::UInt8,
+};
+use ark_relations::r1cs::SynthesisError;
+
+use crate::HASH_LEN;
+
+use super::PoseidonHasher;
+
+/// The R1CS counterpart of [PoseidonHasher], over the BLS12-381 scalar field.
+///
+/// Every method produces the same field element as its native counterpart, so sparse Merkle tree
+/// openings computed under the [super::PoseidonContext] can be verified inside an arkworks
+/// circuit. Nodes are read with [PoseidonHasher::to_field].
+pub struct PoseidonHasherGadget;
+
+impl PoseidonHasherGadget {
+    /// Allocates the parameters of [PoseidonHasher::params] as circuit constants.
+    pub fn params() -> CRHParametersVar<Fr> {
+        CRHParametersVar {
+            parameters: PoseidonHasher::params().clone(),
+        }
+    }
+
+    /// Computes the leaf key of the data under the provided context.
+    pub fn key(
+        params: &CRHParametersVar<Fr>,
+        context: &[UInt8<Fr>],
+        data: &[UInt8<Fr>],
+    ) -> Result<FpVar<Fr>, SynthesisError> {
+        let mut input = Vec::from([
+            FpVar::constant(Fr::from(PoseidonHasher::KEY_PREFIX)),
+            FpVar::constant(Fr::from(context.len() as u64)),
+            FpVar::constant(Fr::from(data.len() as u64)),
+        ]);
+
+        input.extend(context.to_constraint_field()?);
+        input.extend(data.to_constraint_field()?);
+
+        CRHGadget::<Fr>::evaluate(params, &input)
+    }
+
+    /// Computes the leaf node of the data.
+    pub fn hash(
+        params: &CRHParametersVar<Fr>,
+        data: &[UInt8<Fr>],
+    ) -> Result<FpVar<Fr>, SynthesisError> {
+        let mut input = Vec::from([
+            FpVar::constant(Fr::from(PoseidonHasher::DATA_PREFIX)),
+            FpVar::constant(Fr::from(data.len() as u64)),
+        ]);
+
+        input.extend(data.to_constraint_field()?);
+
+        CRHGadget::<Fr>::evaluate(params, &input)
+    }
+
+    /// Computes the parent node of the provided children.
+    pub fn merge(
+        params: &CRHParametersVar<Fr>,
+        a: &FpVar<Fr>,
+        b: &FpVar<Fr>,
+    ) -> Result<FpVar<Fr>, SynthesisError> {
+        let input = [
+            FpVar::constant(Fr::from(PoseidonHasher::MERGE_PREFIX)),
+            a.clone(),
+            b.clone(),
+        ];
+
+        CRHGadget::<Fr>::evaluate(params, &input)
+    }
+
+    /// Returns a boolean that is `true` if the data opens to the root under the provided context.
+    ///
+    /// The opening holds the siblings from the leaf to the root, as in `SmtOpening::opening`. Its
+    /// length is part of the circuit shape.
+    pub fn verify(
+        params: &CRHParametersVar<Fr>,
+        context: &[UInt8<Fr>],
+        root: &FpVar<Fr>,
+        data: &[UInt8<Fr>],
+        opening: &[FpVar<Fr>],
+    ) -> Result<Boolean<Fr>, SynthesisError> {
+        if opening.len() > HASH_LEN * 8 {
+            return Ok(Boolean::FALSE);
+        }
+
+        let key = Self::key(params, context, data)?.to_bits_le()?;
+        let mut node = Self::hash(params, data)?;
+        let mut depth = opening.len();
+
+        for sibling in opening {
+            depth -= 1;
+
+            // the native path reads the key bytes most significant bit first
+            let i = 8 * (depth / 8) + 7 - depth % 8;
+            let bit = if i < Fr::MODULUS_BIT_SIZE as usize {
+                key[i].clone()
+            } else {
+                Boolean::FALSE
+            };
+
+            let left = FpVar::conditionally_select(&bit, sibling, &node)?;
+            let right = FpVar::conditionally_select(&bit, &node, sibling)?;
+
+            node = Self::merge(params, &left, &right)?;
+        }
+
+        node.is_eq(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    use crate::{Hash, Hasher};
+
+    use super::*;
+
+    /// Folds the siblings into a root, as the sparse Merkle tree verification does.
+    fn root(context: &str, data: &[u8], opening: &[Hash]) -> Hash {
+        let key = PoseidonHasher::key(context, data);
+        let mut depth = opening.len();
+
+        opening
+            .iter()
+            .fold(PoseidonHasher::hash(data), |node, sibling| {
+                depth -= 1;
+
+                if (key[depth / 8] >> (7 - depth % 8)) & 1 == 0 {
+                    PoseidonHasher::merge(&node, sibling)
+                } else {
+                    PoseidonHasher::merge(sibling, &node)
+                }
+            })
+    }
+
+    #[test]
+    fn gadget_matches_native_hasher() -> Result<(), SynthesisError> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let params = PoseidonHasherGadget::params();
+
+        let context = "poem";
+        let data: Vec<u8> = (0..70).collect();
+
+        let context_var = UInt8::constant_vec(context.as_bytes());
+        let data_var = UInt8::new_witness_vec(cs.clone(), &data)?;
+
+        let key = PoseidonHasherGadget::key(&params, &context_var, &data_var)?;
+        let hash = PoseidonHasherGadget::hash(&params, &data_var)?;
+        let merge = PoseidonHasherGadget::merge(&params, &key, &hash)?;
+
+        let native_key = PoseidonHasher::key(context, &data);
+        let native_hash = PoseidonHasher::hash(&data);
+
+        assert_eq!(key.value()?, PoseidonHasher::to_field(&native_key));
+        assert_eq!(hash.value()?, PoseidonHasher::to_field(&native_hash));
+        assert_eq!(
+            merge.value()?,
+            PoseidonHasher::to_field(&PoseidonHasher::merge(&native_key, &native_hash))
+        );
+        assert!(cs.is_satisfied()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn gadget_verifies_openings() -> Result<(), SynthesisError> {
+        let context = "poem";
+        let data = b"the quick brown fox";
+        let opening: Vec<Hash> = (0..12u8)
+            .map(|i| match i % 3 {
+                0 => Hash::default(),
+                _ => PoseidonHasher::hash(&[i]),
+            })
+            .collect();
+
+        let expected = root(context, data, &opening);
+
+        for (root, valid) in [(expected, true), (PoseidonHasher::hash(b"other"), false)] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let params = PoseidonHasherGadget::params();
+
+            let context_var = UInt8::constant_vec(context.as_bytes());
+            let data_var = UInt8::new_witness_vec(cs.clone(), data)?;
+            let root_var = FpVar::new_input(cs.clone(), || Ok(PoseidonHasher::to_field(&root)))?;
+            let opening_var = opening
+                .iter()
+                .map(|s| FpVar::new_witness(cs.clone(), || Ok(PoseidonHasher::to_field(s))))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            PoseidonHasherGadget::verify(
+                &params,
+                &context_var,
+                &root_var,
+                &data_var,
+                &opening_var,
+            )?
+            .enforce_equal(&Boolean::TRUE)?;
+
+            assert_eq!(cs.is_satisfied()?, valid);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn gadget_rejects_oversized_openings() -> Result<(), SynthesisError> {
+        let params = PoseidonHasherGadget::params();
+        let opening = vec![FpVar::constant(Fr::from(0u64)); HASH_LEN * 8 + 1];
+
+        let verified = PoseidonHasherGadget::verify(
+            &params,
+            &UInt8::constant_vec(b"poem"),
+            &FpVar::constant(Fr::from(0u64)),
+            &UInt8::constant_vec(b"data"),
+            &opening,
+        )?;
+
+        assert!(!verified.value()?);
+
+        Ok(())
+    }
+}
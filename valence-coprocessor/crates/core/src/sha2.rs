@@ -1,9 +1,12 @@
 use crate::{ExecutionContext, Hash, Hasher};
 use sha2::{Digest, Sha256};
-/// A blake3 hasher implementation for the Valence protocol.
+
+/// A SHA-256 hasher implementation for the Valence protocol.
+///
+/// Guest programs pick up the SP1 SHA-256 precompile through the SP1 patch of the `sha2` crate.
 pub struct Sha2HasherSp1;
 
-/// A blake3 execution environment for the Valence protocol.
+/// A SHA-256 execution environment for the Valence protocol.
 pub struct Sha2Context;
 
 impl Sha2HasherSp1 {
@@ -15,6 +18,8 @@ impl Sha2HasherSp1 {
 }
 
 impl Hasher for Sha2HasherSp1 {
+    type Output = Hash;
+
     fn key(context: &str, data: &[u8]) -> Hash {
         let mut hasher = Sha256::new();
         hasher.update(context);
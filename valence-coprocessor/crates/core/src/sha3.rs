@@ -1,9 +1,13 @@
 use crate::{ExecutionContext, Hash, Hasher};
-use sha3::{Digest, Sha3_384};
-/// A blake3 hasher implementation for the Valence protocol.
+use sha3::{Digest, Sha3_256};
+
+/// A SHA3-256 hasher implementation for the Valence protocol.
+///
+/// Guest programs pick up the accelerated implementation through the SP1 patch of the `sha3`
+/// crate.
 pub struct Sha3HasherSp1;
 
-/// A blake3 execution environment for the Valence protocol.
+/// A SHA3-256 execution environment for the Valence protocol.
 pub struct Sha3Context;
 
 impl Sha3HasherSp1 {
@@ -12,31 +16,41 @@ impl Sha3HasherSp1 {
 
     /// Prefix for node hash.
     pub const MERGE_PREFIX: &[u8] = &[0x01];
+
+    /// Prefix for key hash.
+    pub const KEY_PREFIX: &[u8] = &[0x02];
 }
 
 impl Hasher for Sha3HasherSp1 {
+    type Output = Hash;
+
     fn key(context: &str, data: &[u8]) -> Hash {
-        let mut hasher = Sha3_384::new();
+        let mut hasher = Sha3_256::new();
+        hasher.update(Self::KEY_PREFIX);
+
+        // the context is length-prefixed so it can't bleed into the data
+        hasher.update((context.len() as u64).to_be_bytes());
         hasher.update(context);
 
         hasher.update(data);
-        hasher.finalize().as_slice().try_into().unwrap()
+        hasher.finalize().into()
     }
 
     fn hash(data: &[u8]) -> Hash {
-        let mut hasher = Sha3_384::new();
+        let mut hasher = Sha3_256::new();
         hasher.update(Self::DATA_PREFIX);
 
         hasher.update(data);
-        hasher.finalize().as_slice().try_into().unwrap()
+        hasher.finalize().into()
     }
 
     fn merge(a: &Hash, b: &Hash) -> Hash {
-        let mut hasher = Sha3_384::new();
-        hasher.update(a);
+        let mut hasher = Sha3_256::new();
+        hasher.update(Self::MERGE_PREFIX);
 
+        hasher.update(a);
         hasher.update(b);
-        hasher.finalize().as_slice().try_into().unwrap()
+        hasher.finalize().into()
     }
 }
 
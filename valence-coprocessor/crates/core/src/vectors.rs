@@ -0,0 +1,240 @@
+use core::fmt;
+
+use crate::{Hash, Hasher, HASH_LEN};
+
+/// The conformance test vectors of the hashers, pinning the outputs of every execution context.
+///
+/// Every non-empty line that isn't a `#` comment is a vector in one of the forms:
+///
+/// ```text
+/// <context> key <context-hex> <data-hex> <expected-hex>
+/// <context> hash <data-hex> <expected-hex>
+/// <context> merge <left-hex> <right-hex> <expected-hex>
+/// ```
+///
+/// Empty byte strings are written as `-`.
+pub const HASHER_VECTORS: &str = include_str!("../vectors/hashers.txt");
+
+/// The outcome of a successful [check_hasher_vectors].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VectorReport {
+    /// Number of vectors checked against the hashers.
+    pub checked: usize,
+
+    /// Number of vectors skipped because their context isn't enabled in this build.
+    pub skipped: usize,
+}
+
+/// A failed hasher test vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorError {
+    /// The line isn't a well-formed vector.
+    Malformed {
+        /// One-based line number of the vector.
+        line: usize,
+    },
+
+    /// The hasher output doesn't match the vector.
+    Mismatch {
+        /// One-based line number of the vector.
+        line: usize,
+        /// The output pinned by the vector.
+        expected: Hash,
+        /// The output of the hasher.
+        computed: Hash,
+    },
+}
+
+impl fmt::Display for VectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed { line } => write!(f, "malformed hasher vector at line {line}"),
+            Self::Mismatch {
+                line,
+                expected,
+                computed,
+            } => write!(
+                f,
+                "hasher vector mismatch at line {line}; expected {expected:x?}, computed {computed:x?}"
+            ),
+        }
+    }
+}
+
+/// Checks the test vectors against the hashers enabled in this build.
+///
+/// The vectors are expected in the format of [HASHER_VECTORS]. Contexts are named after their
+/// crate feature: `blake3`, `sha2`, `sha3`, `keccak` and `poseidon`; the vectors of a disabled
+/// context are skipped. Parsing is allocation free, so the check can run inside guest programs.
+pub fn check_hasher_vectors(vectors: &str) -> Result<VectorReport, VectorError> {
+    let mut report = VectorReport::default();
+
+    for (i, text) in vectors.lines().enumerate() {
+        let line = i + 1;
+        let text = text.trim();
+
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = text.split_whitespace();
+        let context = fields.next().unwrap_or_default();
+
+        let computed = match context {
+            #[cfg(feature = "blake3")]
+            "blake3" => compute::<crate::Blake3Hasher>(&mut fields),
+            #[cfg(feature = "sha2")]
+            "sha2" => compute::<crate::Sha2HasherSp1>(&mut fields),
+            #[cfg(feature = "sha3")]
+            "sha3" => compute::<crate::Sha3HasherSp1>(&mut fields),
+            #[cfg(feature = "keccak")]
+            "keccak" => compute::<crate::Keccak256Hasher>(&mut fields),
+            #[cfg(feature = "poseidon")]
+            "poseidon" => compute::<crate::PoseidonHasher>(&mut fields),
+            // only reachable for the contexts disabled in this build
+            #[allow(unreachable_patterns)]
+            "blake3" | "sha2" | "sha3" | "keccak" | "poseidon" => {
+                report.skipped += 1;
+                continue;
+            }
+            _ => None,
+        };
+
+        let expected = fields.next().and_then(hex_to_hash);
+
+        let (computed, expected) = match (computed, expected, fields.next()) {
+            (Some(c), Some(e), None) => (c, e),
+            _ => return Err(VectorError::Malformed { line }),
+        };
+
+        if computed != expected {
+            return Err(VectorError::Mismatch {
+                line,
+                expected,
+                computed,
+            });
+        }
+
+        report.checked += 1;
+    }
+
+    Ok(report)
+}
+
+#[allow(dead_code)]
+fn compute<'a, H: Hasher<Output = Hash>>(
+    fields: &mut impl Iterator<Item = &'a str>,
+) -> Option<Hash> {
+    // the inputs are bounded so the buffers can live on the stack
+    let mut a = [0u8; 256];
+    let mut b = [0u8; 256];
+
+    match fields.next()? {
+        "key" => {
+            let context = hex_to_bytes(fields.next()?, &mut a)?;
+            let context = core::str::from_utf8(context).ok()?;
+            let data = hex_to_bytes(fields.next()?, &mut b)?;
+
+            Some(H::key(context, data))
+        }
+        "hash" => Some(H::hash(hex_to_bytes(fields.next()?, &mut a)?)),
+        "merge" => {
+            let left = hex_to_hash(fields.next()?)?;
+            let right = hex_to_hash(fields.next()?)?;
+
+            Some(H::merge(&left, &right))
+        }
+        _ => None,
+    }
+}
+
+fn hex_to_bytes<'a>(hex: &str, buf: &'a mut [u8]) -> Option<&'a [u8]> {
+    if hex == "-" {
+        return Some(&buf[..0]);
+    }
+
+    let hex = hex.as_bytes();
+    let len = hex.len() / 2;
+
+    if len * 2 != hex.len() || len > buf.len() {
+        return None;
+    }
+
+    for (i, byte) in buf[..len].iter_mut().enumerate() {
+        let hi = (hex[2 * i] as char).to_digit(16)?;
+        let lo = (hex[2 * i + 1] as char).to_digit(16)?;
+
+        *byte = (hi << 4 | lo) as u8;
+    }
+
+    Some(&buf[..len])
+}
+
+fn hex_to_hash(hex: &str) -> Option<Hash> {
+    let mut hash = Hash::default();
+
+    match hex_to_bytes(hex, &mut hash)?.len() {
+        HASH_LEN => Some(hash),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hasher_vectors_match() {
+        let report = check_hasher_vectors(HASHER_VECTORS).unwrap();
+
+        assert!(report.checked > 0);
+
+        #[cfg(all(
+            feature = "blake3",
+            feature = "sha2",
+            feature = "sha3",
+            feature = "keccak",
+            feature = "poseidon"
+        ))]
+        assert_eq!(report.skipped, 0);
+    }
+
+    #[test]
+    fn hasher_vectors_reject_tampered_outputs() {
+        let vectors =
+            "sha2 hash - 0000000000000000000000000000000000000000000000000000000000000000";
+
+        #[cfg(feature = "sha2")]
+        assert!(matches!(
+            check_hasher_vectors(vectors),
+            Err(VectorError::Mismatch { line: 1, .. })
+        ));
+
+        #[cfg(not(feature = "sha2"))]
+        assert_eq!(
+            check_hasher_vectors(vectors),
+            Ok(VectorReport {
+                checked: 0,
+                skipped: 1
+            })
+        );
+    }
+
+    #[test]
+    fn hasher_vectors_reject_malformed_lines() {
+        for vectors in [
+            "sha2",
+            "md5 hash - 00",
+            "sha2 hash 0g -",
+            "sha2 hash - 00 00",
+        ] {
+            let result = check_hasher_vectors(vectors);
+
+            #[cfg(feature = "sha2")]
+            assert_eq!(result, Err(VectorError::Malformed { line: 1 }));
+
+            #[cfg(not(feature = "sha2"))]
+            let _ = result;
+        }
+    }
+}
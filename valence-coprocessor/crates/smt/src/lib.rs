@@ -5,16 +5,24 @@
 extern crate alloc;
 
 use alloc::vec::Vec;
-use valence_coprocessor_core::Hash;
+use valence_coprocessor_core::{Digest, Hash};
 
 mod smt;
+mod snapshot;
+mod witness;
 
 #[cfg(feature = "memory")]
 mod memory;
 
+#[cfg(feature = "std")]
+mod metered;
+
 #[cfg(feature = "rocksdb")]
 mod rocksdb;
 
+#[cfg(feature = "keccak")]
+mod solidity;
+
 #[cfg(test)]
 mod tests;
 
@@ -26,45 +34,63 @@ mod tests;
 /// The trait is agnostic to specific details of the tree implementation and should solely focus on
 /// managing persistence of relationships while making no assumptions whatsoever about the
 /// underlying tree structure itself.
-pub trait TreeBackend {
+pub trait TreeBackend<H: Digest = Hash> {
     /// Appends a relationship from the parent node to its children within a binary tree
     /// structure, returning true if a prior relationship from the parent node was overwritten.
-    fn insert_children(&mut self, parent: &Hash, children: &SmtChildren) -> anyhow::Result<bool>;
+    fn insert_children(&mut self, parent: &H, children: &SmtChildren<H>) -> anyhow::Result<bool>;
 
     /// Fetches the children linked to the provided parent node.
-    fn get_children(&self, parent: &Hash) -> anyhow::Result<Option<SmtChildren>>;
+    fn get_children(&self, parent: &H) -> anyhow::Result<Option<SmtChildren<H>>>;
 
     /// Removes a parent-children relationship from the storage, returning it.
-    fn remove_children(&mut self, parent: &Hash) -> anyhow::Result<Option<SmtChildren>>;
+    fn remove_children(&mut self, parent: &H) -> anyhow::Result<Option<SmtChildren<H>>>;
 
     /// Assign a leaf key to a tree node, logically converting the node into a leaf node,
     /// returning `true` if a prior relationship of the provided node was overwritten.
-    fn insert_node_key(&mut self, node: &Hash, leaf: &Hash) -> anyhow::Result<bool>;
+    fn insert_node_key(&mut self, node: &H, leaf: &H) -> anyhow::Result<bool>;
 
     /// Returns `true` if the provided node is associated with a leaf key.
-    fn has_node_key(&self, node: &Hash) -> anyhow::Result<bool>;
+    fn has_node_key(&self, node: &H) -> anyhow::Result<bool>;
 
     /// Fetches the associated leaf key of the node.
-    fn get_node_key(&self, node: &Hash) -> anyhow::Result<Option<Hash>>;
+    fn get_node_key(&self, node: &H) -> anyhow::Result<Option<H>>;
 
     /// Removes a node to leaf key association from the node, returning it.
-    fn remove_node_key(&mut self, node: &Hash) -> anyhow::Result<Option<Hash>>;
+    fn remove_node_key(&mut self, node: &H) -> anyhow::Result<Option<H>>;
 
     /// Assign a leaf data to a leaf key, returning `true` if a prior relationship of the
     /// provided key to a leaf data was overwritten.
-    fn insert_key_data(&mut self, key: &Hash, data: Vec<u8>) -> anyhow::Result<bool>;
+    fn insert_key_data(&mut self, key: &H, data: Vec<u8>) -> anyhow::Result<bool>;
 
     /// Fetches the associated leaf data to the provided leaf key.
-    fn get_key_data(&self, key: &Hash) -> anyhow::Result<Option<Vec<u8>>>;
+    fn get_key_data(&self, key: &H) -> anyhow::Result<Option<Vec<u8>>>;
 
     /// Removes a leaf key data association, returning it.
-    fn remove_key_data(&mut self, key: &Hash) -> anyhow::Result<Option<Vec<u8>>>;
+    fn remove_key_data(&mut self, key: &H) -> anyhow::Result<Option<Vec<u8>>>;
+
+    /// Assign the count of parent nodes and retained roots that reference the provided node,
+    /// returning `true` if a prior count of the node was overwritten.
+    fn insert_node_refs(&mut self, node: &H, refs: u64) -> anyhow::Result<bool>;
+
+    /// Fetches the count of parent nodes and retained roots that reference the provided node.
+    fn get_node_refs(&self, node: &H) -> anyhow::Result<Option<u64>>;
+
+    /// Removes the reference count of the node, returning it.
+    fn remove_node_refs(&mut self, node: &H) -> anyhow::Result<Option<u64>>;
 }
 
 pub use smt::*;
+pub use snapshot::*;
+pub use witness::*;
 
 #[cfg(feature = "memory")]
 pub use memory::*;
 
+#[cfg(feature = "std")]
+pub use metered::*;
+
 #[cfg(feature = "rocksdb")]
 pub use rocksdb::*;
+
+#[cfg(feature = "keccak")]
+pub use solidity::*;
@@ -1,64 +1,97 @@
 use alloc::vec::Vec;
 use hashbrown::HashMap;
-use valence_coprocessor_core::{Blake3Context, Hash};
+use valence_coprocessor_core::{Blake3Context, Digest, Hash};
 
 use crate::{Smt, SmtChildren, TreeBackend};
 
 /// An ephemeral memory tree associated with a blake3 hash execution environment.
 pub type MemorySmt = Smt<MemoryBackend, Blake3Context>;
 
+/// An ephemeral memory tree associated with a SHA-256 hash execution environment.
+///
+/// Prefer this tree in SP1 guest programs, as SHA-256 is accelerated by a precompile.
+#[cfg(feature = "sha2")]
+pub type Sha256Smt = Smt<MemoryBackend, valence_coprocessor_core::Sha2Context>;
+
 /// An ephemeral memory data backend for concrete sparse Merkle tree usage.
-#[derive(Debug, Default, Clone)]
-pub struct MemoryBackend {
-    children: HashMap<Hash, SmtChildren>,
-    keys: HashMap<Hash, Hash>,
-    data: HashMap<Hash, Vec<u8>>,
+///
+/// Generic over the hash output of the tree, defaulting to a 32 bytes [Hash].
+#[derive(Debug, Clone)]
+pub struct MemoryBackend<H = Hash> {
+    children: HashMap<H, SmtChildren<H>>,
+    keys: HashMap<H, H>,
+    data: HashMap<H, Vec<u8>>,
+    refs: HashMap<H, u64>,
+}
+
+impl<H> Default for MemoryBackend<H> {
+    fn default() -> Self {
+        Self {
+            children: HashMap::new(),
+            keys: HashMap::new(),
+            data: HashMap::new(),
+            refs: HashMap::new(),
+        }
+    }
 }
 
-impl TreeBackend for MemoryBackend {
-    fn insert_children(&mut self, parent: &Hash, children: &SmtChildren) -> anyhow::Result<bool> {
+impl<H: Digest> TreeBackend<H> for MemoryBackend<H> {
+    fn insert_children(&mut self, parent: &H, children: &SmtChildren<H>) -> anyhow::Result<bool> {
         Ok(self.children.insert(*parent, *children).is_some())
     }
 
-    fn get_children(&self, parent: &Hash) -> anyhow::Result<Option<SmtChildren>> {
+    fn get_children(&self, parent: &H) -> anyhow::Result<Option<SmtChildren<H>>> {
         Ok(self.children.get(parent).copied())
     }
 
-    fn remove_children(&mut self, parent: &Hash) -> anyhow::Result<Option<SmtChildren>> {
+    fn remove_children(&mut self, parent: &H) -> anyhow::Result<Option<SmtChildren<H>>> {
         Ok(self.children.remove(parent))
     }
 
-    fn insert_node_key(&mut self, node: &Hash, leaf: &Hash) -> anyhow::Result<bool> {
+    fn insert_node_key(&mut self, node: &H, leaf: &H) -> anyhow::Result<bool> {
         Ok(self.keys.insert(*node, *leaf).is_some())
     }
 
-    fn has_node_key(&self, node: &Hash) -> anyhow::Result<bool> {
+    fn has_node_key(&self, node: &H) -> anyhow::Result<bool> {
         Ok(self.keys.get(node).is_some())
     }
 
-    fn get_node_key(&self, node: &Hash) -> anyhow::Result<Option<Hash>> {
+    fn get_node_key(&self, node: &H) -> anyhow::Result<Option<H>> {
         Ok(self.keys.get(node).copied())
     }
 
-    fn remove_node_key(&mut self, node: &Hash) -> anyhow::Result<Option<Hash>> {
+    fn remove_node_key(&mut self, node: &H) -> anyhow::Result<Option<H>> {
         Ok(self.keys.remove(node))
     }
 
-    fn insert_key_data(&mut self, key: &Hash, data: Vec<u8>) -> anyhow::Result<bool> {
+    fn insert_key_data(&mut self, key: &H, data: Vec<u8>) -> anyhow::Result<bool> {
         Ok(self.data.insert(*key, data).is_some())
     }
 
-    fn get_key_data(&self, key: &Hash) -> anyhow::Result<Option<Vec<u8>>> {
+    fn get_key_data(&self, key: &H) -> anyhow::Result<Option<Vec<u8>>> {
         Ok(self.data.get(key).cloned())
     }
 
-    fn remove_key_data(&mut self, key: &Hash) -> anyhow::Result<Option<Vec<u8>>> {
+    fn remove_key_data(&mut self, key: &H) -> anyhow::Result<Option<Vec<u8>>> {
         Ok(self.data.remove(key))
     }
+
+    fn insert_node_refs(&mut self, node: &H, refs: u64) -> anyhow::Result<bool> {
+        Ok(self.refs.insert(*node, refs).is_some())
+    }
+
+    fn get_node_refs(&self, node: &H) -> anyhow::Result<Option<u64>> {
+        Ok(self.refs.get(node).copied())
+    }
+
+    fn remove_node_refs(&mut self, node: &H) -> anyhow::Result<Option<u64>> {
+        Ok(self.refs.remove(node))
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use alloc::vec;
     use valence_coprocessor_core::{Blake3Hasher, Hasher as _};
 
     use crate::SmtOpening;
@@ -393,4 +426,77 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn prune_retains_shared_nodes() -> anyhow::Result<()> {
+        let context = "poem";
+        let data = [
+            [0x00, 0x00, 0x09],
+            [0x00, 0x00, 0x19],
+            [0x00, 0x00, 0x03],
+            [0x00, 0x00, 0x05],
+        ];
+
+        let mut tree = MemorySmt::default();
+        let root = MemorySmt::empty_tree_root();
+
+        let single = tree.insert(root, context, data[0].to_vec())?;
+        let root = tree.insert(single, context, data[1].to_vec())?;
+        let shared = tree.insert(root, context, data[2].to_vec())?;
+
+        // two roots branching from a common ancestor
+        let a = tree.insert(shared, context, data[3].to_vec())?;
+        let b = tree.insert(shared, context, b"Hope is the thing with feathers".to_vec())?;
+
+        // the single leaf root is a child of the newer root
+        tree.prune(&root)?;
+
+        let proof = tree.get_opening(context, single, &data[0])?.unwrap();
+
+        assert!(MemorySmt::verify(context, &single, &proof));
+
+        tree.prune(&single)?;
+        tree.prune(&shared)?;
+        tree.prune(&a)?;
+
+        for d in &data[..3] {
+            let proof = tree.get_opening(context, b, d)?.unwrap();
+
+            assert!(MemorySmt::verify(context, &b, &proof));
+        }
+
+        let proof = tree
+            .get_opening(context, b, b"Hope is the thing with feathers")?
+            .unwrap();
+
+        assert!(MemorySmt::verify(context, &b, &proof));
+        assert!(tree.get_opening(context, b, &data[3])?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn prune_all_roots_empties_backend() -> anyhow::Result<()> {
+        let context = "property";
+        let mut tree = MemorySmt::default();
+        let mut roots = vec![MemorySmt::empty_tree_root()];
+
+        for n in [1778514084u32, 252724253, 45104643, 7, 13] {
+            let root = tree.insert(*roots.last().unwrap(), context, n.to_le_bytes().to_vec())?;
+
+            roots.push(root);
+        }
+
+        // interleave so shared nodes are released by different roots
+        for i in [3, 1, 5, 2, 4, 0] {
+            tree.prune(&roots[i])?;
+        }
+
+        assert!(tree.b.children.is_empty());
+        assert!(tree.b.keys.is_empty());
+        assert!(tree.b.data.is_empty());
+        assert!(tree.b.refs.is_empty());
+
+        Ok(())
+    }
 }
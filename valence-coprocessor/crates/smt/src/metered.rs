@@ -0,0 +1,655 @@
+use core::{
+    mem,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use std::time::Instant;
+
+use alloc::vec::Vec;
+use valence_coprocessor_core::Digest;
+
+use crate::{SmtChildren, TreeBackend};
+
+/// A [TreeBackend] method, as accounted by the [MeteredBackend].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BackendOp {
+    /// [TreeBackend::insert_children]
+    InsertChildren,
+    /// [TreeBackend::get_children]
+    GetChildren,
+    /// [TreeBackend::remove_children]
+    RemoveChildren,
+    /// [TreeBackend::insert_node_key]
+    InsertNodeKey,
+    /// [TreeBackend::has_node_key]
+    HasNodeKey,
+    /// [TreeBackend::get_node_key]
+    GetNodeKey,
+    /// [TreeBackend::remove_node_key]
+    RemoveNodeKey,
+    /// [TreeBackend::insert_key_data]
+    InsertKeyData,
+    /// [TreeBackend::get_key_data]
+    GetKeyData,
+    /// [TreeBackend::remove_key_data]
+    RemoveKeyData,
+    /// [TreeBackend::insert_node_refs]
+    InsertNodeRefs,
+    /// [TreeBackend::get_node_refs]
+    GetNodeRefs,
+    /// [TreeBackend::remove_node_refs]
+    RemoveNodeRefs,
+}
+
+impl BackendOp {
+    /// Total number of backend methods.
+    pub const COUNT: usize = 13;
+
+    /// All the backend methods, indexed by their discriminant.
+    pub const ALL: [Self; Self::COUNT] = [
+        Self::InsertChildren,
+        Self::GetChildren,
+        Self::RemoveChildren,
+        Self::InsertNodeKey,
+        Self::HasNodeKey,
+        Self::GetNodeKey,
+        Self::RemoveNodeKey,
+        Self::InsertKeyData,
+        Self::GetKeyData,
+        Self::RemoveKeyData,
+        Self::InsertNodeRefs,
+        Self::GetNodeRefs,
+        Self::RemoveNodeRefs,
+    ];
+
+    /// Returns the method name, as used for the `op` label of the exported metrics.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::InsertChildren => "insert_children",
+            Self::GetChildren => "get_children",
+            Self::RemoveChildren => "remove_children",
+            Self::InsertNodeKey => "insert_node_key",
+            Self::HasNodeKey => "has_node_key",
+            Self::GetNodeKey => "get_node_key",
+            Self::RemoveNodeKey => "remove_node_key",
+            Self::InsertKeyData => "insert_key_data",
+            Self::GetKeyData => "get_key_data",
+            Self::RemoveKeyData => "remove_key_data",
+            Self::InsertNodeRefs => "insert_node_refs",
+            Self::GetNodeRefs => "get_node_refs",
+            Self::RemoveNodeRefs => "remove_node_refs",
+        }
+    }
+
+    /// Returns `true` if the method doesn't mutate the backend.
+    pub const fn is_read(&self) -> bool {
+        matches!(
+            self,
+            Self::GetChildren
+                | Self::HasNodeKey
+                | Self::GetNodeKey
+                | Self::GetKeyData
+                | Self::GetNodeRefs
+        )
+    }
+}
+
+/// A latency histogram with power of two nanoseconds buckets.
+///
+/// The bucket `i` counts the calls that took `[2^i, 2^(i+1))` nanoseconds, with the first bucket
+/// also including the calls that took less than a nanosecond, and the last bucket including all
+/// the calls above its lower bound.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// Number of calls per bucket.
+    pub buckets: [u64; LatencyHistogram::BUCKETS],
+}
+
+impl LatencyHistogram {
+    /// Number of buckets of the histogram.
+    pub const BUCKETS: usize = 32;
+
+    /// Returns the bucket index of the provided latency.
+    pub fn bucket(latency: Duration) -> usize {
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+
+        (nanos.checked_ilog2().unwrap_or(0) as usize).min(Self::BUCKETS - 1)
+    }
+
+    /// Returns the exclusive upper bound of the provided bucket.
+    pub fn upper_bound(bucket: usize) -> Duration {
+        match bucket {
+            b if b + 1 >= Self::BUCKETS => Duration::MAX,
+            b => Duration::from_nanos(1 << (b + 1)),
+        }
+    }
+
+    /// Total number of recorded calls.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Returns the upper bound of the bucket that contains the provided quantile `q` in `[0, 1]`.
+    pub fn quantile(&self, q: f64) -> Duration {
+        let count = self.count();
+        let target = ((count as f64) * q.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+        let mut seen = 0;
+
+        for (i, b) in self.buckets.iter().enumerate() {
+            seen += b;
+
+            if seen >= target {
+                return Self::upper_bound(i);
+            }
+        }
+
+        Duration::ZERO
+    }
+}
+
+/// The accounted metrics of a single backend method.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OpMetrics {
+    /// Number of calls.
+    pub calls: u64,
+    /// Number of calls that returned an error.
+    pub errors: u64,
+    /// Bytes of nodes, keys and data returned by the method.
+    pub bytes_read: u64,
+    /// Bytes of nodes, keys and data handed to the method.
+    pub bytes_written: u64,
+    /// Accumulated latency of all the calls.
+    pub latency_total: Duration,
+    /// Latency distribution of the calls.
+    pub latency: LatencyHistogram,
+}
+
+/// A snapshot of the metrics of a [MeteredBackend].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BackendMetrics {
+    /// Metrics per method, indexed as [BackendOp::ALL].
+    pub ops: [OpMetrics; BackendOp::COUNT],
+}
+
+impl BackendMetrics {
+    /// Returns the metrics of the provided method.
+    pub fn op(&self, op: BackendOp) -> &OpMetrics {
+        &self.ops[op as usize]
+    }
+
+    /// Total number of calls.
+    pub fn calls(&self) -> u64 {
+        self.ops.iter().map(|o| o.calls).sum()
+    }
+
+    /// Total number of calls to methods that don't mutate the backend.
+    pub fn reads(&self) -> u64 {
+        BackendOp::ALL
+            .iter()
+            .filter(|op| op.is_read())
+            .map(|op| self.op(*op).calls)
+            .sum()
+    }
+
+    /// Total number of calls to methods that mutate the backend.
+    pub fn writes(&self) -> u64 {
+        self.calls() - self.reads()
+    }
+
+    /// Total bytes returned by the backend.
+    pub fn bytes_read(&self) -> u64 {
+        self.ops.iter().map(|o| o.bytes_read).sum()
+    }
+
+    /// Total bytes handed to the backend.
+    pub fn bytes_written(&self) -> u64 {
+        self.ops.iter().map(|o| o.bytes_written).sum()
+    }
+}
+
+#[derive(Debug, Default)]
+struct OpCounters {
+    calls: AtomicU64,
+    errors: AtomicU64,
+    bytes_read: AtomicU64,
+    bytes_written: AtomicU64,
+    latency_total: AtomicU64,
+    latency: [AtomicU64; LatencyHistogram::BUCKETS],
+}
+
+impl OpCounters {
+    fn load(&self) -> OpMetrics {
+        OpMetrics {
+            calls: self.calls.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            latency_total: Duration::from_nanos(self.latency_total.load(Ordering::Relaxed)),
+            latency: LatencyHistogram {
+                buckets: core::array::from_fn(|i| self.latency[i].load(Ordering::Relaxed)),
+            },
+        }
+    }
+
+    fn store(&self, metrics: &OpMetrics) {
+        let latency_total = u64::try_from(metrics.latency_total.as_nanos()).unwrap_or(u64::MAX);
+
+        self.calls.store(metrics.calls, Ordering::Relaxed);
+        self.errors.store(metrics.errors, Ordering::Relaxed);
+        self.bytes_read.store(metrics.bytes_read, Ordering::Relaxed);
+        self.bytes_written
+            .store(metrics.bytes_written, Ordering::Relaxed);
+        self.latency_total.store(latency_total, Ordering::Relaxed);
+
+        for (b, v) in self.latency.iter().zip(metrics.latency.buckets.iter()) {
+            b.store(*v, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A [TreeBackend] wrapper that accounts every call to the inner backend.
+///
+/// Each method is accounted by number of calls, errors, latency distribution, and bytes of nodes,
+/// keys and data read from or written to the backend. The lookup arguments are not accounted as
+/// bytes. The counters are atomic, so read-only methods can be accounted behind shared references.
+///
+/// With the `metrics` feature, every call is also reported to the global `metrics` recorder as
+/// the `valence_smt_backend_calls_total`, `valence_smt_backend_errors_total`,
+/// `valence_smt_backend_read_bytes_total`, `valence_smt_backend_written_bytes_total` counters and
+/// the `valence_smt_backend_latency_seconds` histogram, all labeled by `op`.
+///
+/// # Example
+///
+/// ```rust
+/// #[cfg(feature = "memory")]
+/// fn run() -> anyhow::Result<()> {
+///     use valence_coprocessor_core::Blake3Context;
+///     use valence_smt::{BackendOp, MemoryBackend, MeteredBackend, Smt};
+///
+///     type MeteredSmt = Smt<MeteredBackend<MemoryBackend>, Blake3Context>;
+///
+///     // wraps the backend to account its calls
+///     let mut tree = MeteredSmt::from(MeteredBackend::new(MemoryBackend::default()));
+///     let root = tree.insert(MeteredSmt::empty_tree_root(), "foo", b"bar".to_vec())?;
+///
+///     // discards the metrics of the insertion
+///     tree.backend().reset();
+///     tree.get_opening("foo", root, b"bar")?;
+///
+///     let metrics = tree.backend().metrics();
+///
+///     assert_eq!(metrics.op(BackendOp::GetKeyData).calls, 1);
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct MeteredBackend<B> {
+    inner: B,
+    counters: [OpCounters; BackendOp::COUNT],
+}
+
+impl<B> MeteredBackend<B> {
+    /// Wraps the provided backend with zeroed counters.
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            counters: Default::default(),
+        }
+    }
+
+    /// Returns the inner backend.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Returns the inner backend as mutable; calls made through it are not accounted.
+    pub fn inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    /// Unwraps the inner backend, discarding the metrics.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Returns a snapshot of the accounted metrics.
+    pub fn metrics(&self) -> BackendMetrics {
+        BackendMetrics {
+            ops: core::array::from_fn(|i| self.counters[i].load()),
+        }
+    }
+
+    /// Zeroes all the counters, returning the metrics accounted until now.
+    pub fn reset(&self) -> BackendMetrics {
+        let metrics = self.metrics();
+
+        self.counters
+            .iter()
+            .for_each(|c| c.store(&OpMetrics::default()));
+
+        metrics
+    }
+
+    fn record<T>(
+        &self,
+        op: BackendOp,
+        start: Instant,
+        res: &anyhow::Result<T>,
+        read: impl FnOnce(&T) -> usize,
+        written: usize,
+    ) {
+        let elapsed = start.elapsed();
+        let counters = &self.counters[op as usize];
+        let error = res.is_err();
+        let read = res.as_ref().map(read).unwrap_or(0) as u64;
+        let written = written as u64;
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+
+        counters.calls.fetch_add(1, Ordering::Relaxed);
+        counters.errors.fetch_add(error as u64, Ordering::Relaxed);
+        counters.bytes_read.fetch_add(read, Ordering::Relaxed);
+        counters.bytes_written.fetch_add(written, Ordering::Relaxed);
+        counters.latency_total.fetch_add(nanos, Ordering::Relaxed);
+        counters.latency[LatencyHistogram::bucket(elapsed)].fetch_add(1, Ordering::Relaxed);
+
+        #[cfg(feature = "metrics")]
+        {
+            let op = op.as_str();
+
+            metrics::counter!("valence_smt_backend_calls_total", "op" => op).increment(1);
+            metrics::counter!("valence_smt_backend_errors_total", "op" => op)
+                .increment(error as u64);
+            metrics::counter!("valence_smt_backend_read_bytes_total", "op" => op).increment(read);
+            metrics::counter!("valence_smt_backend_written_bytes_total", "op" => op)
+                .increment(written);
+            metrics::histogram!("valence_smt_backend_latency_seconds", "op" => op)
+                .record(elapsed.as_secs_f64());
+        }
+    }
+}
+
+impl<B: Clone> Clone for MeteredBackend<B> {
+    fn clone(&self) -> Self {
+        let metered = Self::new(self.inner.clone());
+
+        for (c, m) in metered.counters.iter().zip(self.metrics().ops.iter()) {
+            c.store(m);
+        }
+
+        metered
+    }
+}
+
+impl<B: TreeBackend> From<B> for MeteredBackend<B> {
+    fn from(inner: B) -> Self {
+        Self::new(inner)
+    }
+}
+
+const REFS_LEN: usize = mem::size_of::<u64>();
+
+impl<H, B> TreeBackend<H> for MeteredBackend<B>
+where
+    H: Digest,
+    B: TreeBackend<H>,
+{
+    fn insert_children(&mut self, parent: &H, children: &SmtChildren<H>) -> anyhow::Result<bool> {
+        let start = Instant::now();
+        let res = self.inner.insert_children(parent, children);
+
+        self.record(BackendOp::InsertChildren, start, &res, |_| 0, 2 * H::LEN);
+
+        res
+    }
+
+    fn get_children(&self, parent: &H) -> anyhow::Result<Option<SmtChildren<H>>> {
+        let start = Instant::now();
+        let res = self.inner.get_children(parent);
+
+        self.record(
+            BackendOp::GetChildren,
+            start,
+            &res,
+            |c| c.map(|_| 2 * H::LEN).unwrap_or(0),
+            0,
+        );
+
+        res
+    }
+
+    fn remove_children(&mut self, parent: &H) -> anyhow::Result<Option<SmtChildren<H>>> {
+        let start = Instant::now();
+        let res = self.inner.remove_children(parent);
+
+        self.record(
+            BackendOp::RemoveChildren,
+            start,
+            &res,
+            |c| c.map(|_| 2 * H::LEN).unwrap_or(0),
+            0,
+        );
+
+        res
+    }
+
+    fn insert_node_key(&mut self, node: &H, leaf: &H) -> anyhow::Result<bool> {
+        let start = Instant::now();
+        let res = self.inner.insert_node_key(node, leaf);
+
+        self.record(BackendOp::InsertNodeKey, start, &res, |_| 0, H::LEN);
+
+        res
+    }
+
+    fn has_node_key(&self, node: &H) -> anyhow::Result<bool> {
+        let start = Instant::now();
+        let res = self.inner.has_node_key(node);
+
+        self.record(BackendOp::HasNodeKey, start, &res, |_| 0, 0);
+
+        res
+    }
+
+    fn get_node_key(&self, node: &H) -> anyhow::Result<Option<H>> {
+        let start = Instant::now();
+        let res = self.inner.get_node_key(node);
+
+        self.record(
+            BackendOp::GetNodeKey,
+            start,
+            &res,
+            |k| k.map(|_| H::LEN).unwrap_or(0),
+            0,
+        );
+
+        res
+    }
+
+    fn remove_node_key(&mut self, node: &H) -> anyhow::Result<Option<H>> {
+        let start = Instant::now();
+        let res = self.inner.remove_node_key(node);
+
+        self.record(
+            BackendOp::RemoveNodeKey,
+            start,
+            &res,
+            |k| k.map(|_| H::LEN).unwrap_or(0),
+            0,
+        );
+
+        res
+    }
+
+    fn insert_key_data(&mut self, key: &H, data: Vec<u8>) -> anyhow::Result<bool> {
+        let written = data.len();
+        let start = Instant::now();
+        let res = self.inner.insert_key_data(key, data);
+
+        self.record(BackendOp::InsertKeyData, start, &res, |_| 0, written);
+
+        res
+    }
+
+    fn get_key_data(&self, key: &H) -> anyhow::Result<Option<Vec<u8>>> {
+        let start = Instant::now();
+        let res = self.inner.get_key_data(key);
+
+        self.record(
+            BackendOp::GetKeyData,
+            start,
+            &res,
+            |d| d.as_ref().map(Vec::len).unwrap_or(0),
+            0,
+        );
+
+        res
+    }
+
+    fn remove_key_data(&mut self, key: &H) -> anyhow::Result<Option<Vec<u8>>> {
+        let start = Instant::now();
+        let res = self.inner.remove_key_data(key);
+
+        self.record(
+            BackendOp::RemoveKeyData,
+            start,
+            &res,
+            |d| d.as_ref().map(Vec::len).unwrap_or(0),
+            0,
+        );
+
+        res
+    }
+
+    fn insert_node_refs(&mut self, node: &H, refs: u64) -> anyhow::Result<bool> {
+        let start = Instant::now();
+        let res = self.inner.insert_node_refs(node, refs);
+
+        self.record(BackendOp::InsertNodeRefs, start, &res, |_| 0, REFS_LEN);
+
+        res
+    }
+
+    fn get_node_refs(&self, node: &H) -> anyhow::Result<Option<u64>> {
+        let start = Instant::now();
+        let res = self.inner.get_node_refs(node);
+
+        self.record(
+            BackendOp::GetNodeRefs,
+            start,
+            &res,
+            |r| r.map(|_| REFS_LEN).unwrap_or(0),
+            0,
+        );
+
+        res
+    }
+
+    fn remove_node_refs(&mut self, node: &H) -> anyhow::Result<Option<u64>> {
+        let start = Instant::now();
+        let res = self.inner.remove_node_refs(node);
+
+        self.record(
+            BackendOp::RemoveNodeRefs,
+            start,
+            &res,
+            |r| r.map(|_| REFS_LEN).unwrap_or(0),
+            0,
+        );
+
+        res
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use valence_coprocessor_core::{Blake3Context, HASH_LEN};
+
+    use crate::{MemoryBackend, Smt};
+
+    use super::*;
+
+    type MeteredSmt = Smt<MeteredBackend<MemoryBackend>, Blake3Context>;
+
+    #[test]
+    fn accounts_single_leaf() -> anyhow::Result<()> {
+        let data = b"Two roads diverged in a wood, and I took the one less traveled by";
+
+        let mut tree = MeteredSmt::from(MeteredBackend::new(MemoryBackend::default()));
+        let root = tree.insert(MeteredSmt::empty_tree_root(), "poem", data.to_vec())?;
+
+        let metrics = tree.backend().reset();
+
+        assert_eq!(metrics.op(BackendOp::InsertKeyData).calls, 1);
+        assert_eq!(metrics.op(BackendOp::InsertNodeKey).calls, 1);
+        assert_eq!(metrics.op(BackendOp::GetNodeRefs).calls, 1);
+        assert_eq!(metrics.op(BackendOp::InsertNodeRefs).calls, 1);
+        assert_eq!(metrics.writes(), 3);
+        assert_eq!(metrics.reads(), 1);
+        assert_eq!(
+            metrics.bytes_written(),
+            (data.len() + HASH_LEN + REFS_LEN) as u64
+        );
+
+        tree.get_opening("poem", root, data)?.unwrap();
+
+        let metrics = tree.backend().metrics();
+
+        assert_eq!(metrics.op(BackendOp::GetKeyData).calls, 1);
+        assert_eq!(metrics.op(BackendOp::GetChildren).calls, 1);
+        assert_eq!(metrics.writes(), 0);
+        assert_eq!(metrics.bytes_read(), data.len() as u64);
+        assert_eq!(
+            metrics.op(BackendOp::GetKeyData).latency.count(),
+            metrics.op(BackendOp::GetKeyData).calls
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn accounts_opening_depth() -> anyhow::Result<()> {
+        let context = "poem";
+        let data = [
+            [0x00, 0x00, 0x09],
+            [0x00, 0x00, 0x19],
+            [0x00, 0x00, 0x03],
+            [0x00, 0x00, 0x05],
+        ];
+
+        let mut tree = MeteredSmt::from(MeteredBackend::new(MemoryBackend::default()));
+        let mut root = MeteredSmt::empty_tree_root();
+
+        for d in data {
+            root = tree.insert(root, context, d.to_vec())?;
+        }
+
+        for d in data {
+            tree.backend().reset();
+
+            let proof = tree.get_opening(context, root, &d)?.unwrap();
+            let metrics = tree.backend().metrics();
+
+            // one lookup per traversed node, plus the leaf that terminates the traversal
+            assert_eq!(
+                metrics.op(BackendOp::GetChildren).calls,
+                proof.opening.len() as u64 + 1
+            );
+            assert_eq!(metrics.writes(), 0);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn clone_preserves_metrics() -> anyhow::Result<()> {
+        let mut backend = MeteredBackend::new(MemoryBackend::default());
+
+        backend.insert_key_data(&[1; 32], b"foo".to_vec())?;
+        backend.get_key_data(&[1; 32])?;
+
+        assert_eq!(backend.clone().metrics(), backend.metrics());
+
+        Ok(())
+    }
+}
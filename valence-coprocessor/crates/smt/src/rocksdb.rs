@@ -3,8 +3,7 @@ use std::path::Path;
 
 use alloc::vec::Vec;
 use rocksdb::{Options, SliceTransform, DB};
-use valence_coprocessor_core::Hash;
-use zerocopy::TryFromBytes;
+use valence_coprocessor_core::Digest;
 
 use crate::{SmtChildren, TreeBackend};
 
@@ -23,6 +22,9 @@ impl RocksBackend {
     /// Key prefix for node-leaf data relationship.
     pub const PREFIX_DATA: &[u8] = b"data:";
 
+    /// Key prefix for node reference count.
+    pub const PREFIX_REFS: &[u8] = b"refs:";
+
     /// Opens a new RocksDB tree backend.
     pub fn open<P>(path: P) -> anyhow::Result<Self>
     where
@@ -55,40 +57,40 @@ impl DerefMut for RocksBackend {
     }
 }
 
-impl TreeBackend for RocksBackend {
-    fn insert_children(&mut self, parent: &Hash, children: &SmtChildren) -> anyhow::Result<bool> {
-        let key = [Self::PREFIX_NODE, parent].concat();
+impl<H: Digest> TreeBackend<H> for RocksBackend {
+    fn insert_children(&mut self, parent: &H, children: &SmtChildren<H>) -> anyhow::Result<bool> {
+        let key = [Self::PREFIX_NODE, parent.as_ref()].concat();
         let replaced = self.get(&key)?.is_some();
 
-        self.put(&key, children)?;
+        self.put(&key, children.to_bytes())?;
 
         Ok(replaced)
     }
 
-    fn get_children(&self, parent: &Hash) -> anyhow::Result<Option<SmtChildren>> {
-        let key = [Self::PREFIX_NODE, parent].concat();
+    fn get_children(&self, parent: &H) -> anyhow::Result<Option<SmtChildren<H>>> {
+        let key = [Self::PREFIX_NODE, parent.as_ref()].concat();
         let bytes = match self.get(&key)? {
             Some(b) => b,
             None => return Ok(None),
         };
 
-        let c = SmtChildren::try_read_from_bytes(bytes.as_slice())
-            .map_err(|_| anyhow::anyhow!("inconsistent children bytes"))?;
+        let c = SmtChildren::from_bytes(bytes.as_slice())
+            .ok_or_else(|| anyhow::anyhow!("inconsistent children bytes"))?;
 
         Ok(Some(c))
     }
 
-    fn remove_children(&mut self, parent: &Hash) -> anyhow::Result<Option<SmtChildren>> {
+    fn remove_children(&mut self, parent: &H) -> anyhow::Result<Option<SmtChildren<H>>> {
         let children = self.get_children(parent)?;
-        let key = [Self::PREFIX_NODE, parent].concat();
+        let key = [Self::PREFIX_NODE, parent.as_ref()].concat();
 
         self.delete(&key)?;
 
         Ok(children)
     }
 
-    fn insert_node_key(&mut self, node: &Hash, leaf: &Hash) -> anyhow::Result<bool> {
-        let key = [Self::PREFIX_KEY, node].concat();
+    fn insert_node_key(&mut self, node: &H, leaf: &H) -> anyhow::Result<bool> {
+        let key = [Self::PREFIX_KEY, node.as_ref()].concat();
         let replaced = self.get(&key)?.is_some();
 
         self.put(&key, leaf)?;
@@ -96,36 +98,36 @@ impl TreeBackend for RocksBackend {
         Ok(replaced)
     }
 
-    fn has_node_key(&self, node: &Hash) -> anyhow::Result<bool> {
-        let key = [Self::PREFIX_KEY, node].concat();
+    fn has_node_key(&self, node: &H) -> anyhow::Result<bool> {
+        let key = [Self::PREFIX_KEY, node.as_ref()].concat();
 
         Ok(self.get(&key)?.is_some())
     }
 
-    fn get_node_key(&self, node: &Hash) -> anyhow::Result<Option<Hash>> {
-        let key = [Self::PREFIX_KEY, node].concat();
+    fn get_node_key(&self, node: &H) -> anyhow::Result<Option<H>> {
+        let key = [Self::PREFIX_KEY, node.as_ref()].concat();
         let bytes = match self.get(&key)? {
             Some(b) => b,
             None => return Ok(None),
         };
 
-        let c = Hash::try_read_from_bytes(bytes.as_slice())
-            .map_err(|_| anyhow::anyhow!("inconsistent node key bytes"))?;
+        let c = H::from_slice(bytes.as_slice())
+            .ok_or_else(|| anyhow::anyhow!("inconsistent node key bytes"))?;
 
         Ok(Some(c))
     }
 
-    fn remove_node_key(&mut self, node: &Hash) -> anyhow::Result<Option<Hash>> {
+    fn remove_node_key(&mut self, node: &H) -> anyhow::Result<Option<H>> {
         let node_key = self.get_node_key(node)?;
-        let key = [Self::PREFIX_NODE, node].concat();
+        let key = [Self::PREFIX_KEY, node.as_ref()].concat();
 
         self.delete(&key)?;
 
         Ok(node_key)
     }
 
-    fn insert_key_data(&mut self, key: &Hash, data: Vec<u8>) -> anyhow::Result<bool> {
-        let key = [Self::PREFIX_DATA, key].concat();
+    fn insert_key_data(&mut self, key: &H, data: Vec<u8>) -> anyhow::Result<bool> {
+        let key = [Self::PREFIX_DATA, key.as_ref()].concat();
         let replaced = self.get(&key)?.is_some();
 
         self.put(&key, data)?;
@@ -133,20 +135,51 @@ impl TreeBackend for RocksBackend {
         Ok(replaced)
     }
 
-    fn get_key_data(&self, key: &Hash) -> anyhow::Result<Option<Vec<u8>>> {
-        let key = [Self::PREFIX_DATA, key].concat();
+    fn get_key_data(&self, key: &H) -> anyhow::Result<Option<Vec<u8>>> {
+        let key = [Self::PREFIX_DATA, key.as_ref()].concat();
 
         Ok(self.get(&key)?)
     }
 
-    fn remove_key_data(&mut self, key: &Hash) -> anyhow::Result<Option<Vec<u8>>> {
+    fn remove_key_data(&mut self, key: &H) -> anyhow::Result<Option<Vec<u8>>> {
         let data = self.get_key_data(key)?;
-        let key = [Self::PREFIX_DATA, key].concat();
+        let key = [Self::PREFIX_DATA, key.as_ref()].concat();
 
         self.delete(&key)?;
 
         Ok(data)
     }
+
+    fn insert_node_refs(&mut self, node: &H, refs: u64) -> anyhow::Result<bool> {
+        let key = [Self::PREFIX_REFS, node.as_ref()].concat();
+        let replaced = self.get(&key)?.is_some();
+
+        self.put(&key, refs.to_le_bytes())?;
+
+        Ok(replaced)
+    }
+
+    fn get_node_refs(&self, node: &H) -> anyhow::Result<Option<u64>> {
+        let key = [Self::PREFIX_REFS, node.as_ref()].concat();
+        let bytes = match self.get(&key)? {
+            Some(b) => b,
+            None => return Ok(None),
+        };
+
+        let refs = <[u8; 8]>::try_from(bytes.as_slice())
+            .map_err(|_| anyhow::anyhow!("inconsistent node refs bytes"))?;
+
+        Ok(Some(u64::from_le_bytes(refs)))
+    }
+
+    fn remove_node_refs(&mut self, node: &H) -> anyhow::Result<Option<u64>> {
+        let refs = self.get_node_refs(node)?;
+        let key = [Self::PREFIX_REFS, node.as_ref()].concat();
+
+        self.delete(&key)?;
+
+        Ok(refs)
+    }
 }
 
 #[test]
@@ -160,4 +193,9 @@ fn rocksdb_prefix_are_uniform() {
         RocksBackend::PREFIX_NODE.len(),
         RocksBackend::PREFIX_DATA.len()
     );
+
+    assert_eq!(
+        RocksBackend::PREFIX_NODE.len(),
+        RocksBackend::PREFIX_REFS.len()
+    );
 }
@@ -2,8 +2,7 @@ use core::marker::PhantomData;
 
 use alloc::vec::Vec;
 use borsh::{BorshDeserialize, BorshSerialize};
-use valence_coprocessor_core::{ExecutionContext, Hash, Hasher, HASH_LEN};
-use zerocopy::IntoBytes as _;
+use valence_coprocessor_core::{Digest, ExecutionContext, Hash, HashOf, Hasher};
 
 use crate::TreeBackend;
 
@@ -36,7 +35,8 @@ use crate::TreeBackend;
 /// bit by bit, progressively moving from the most significant bit (MSB). If the current bit is 0,
 /// traversal shifts to the left; if it's 1, it moves to the right.
 ///
-/// The implementation is collision safe up to [HASH_LEN] bytes.
+/// The implementation is collision safe up to the byte-length of the hasher output (i.e.
+/// [Digest::LEN]).
 ///
 /// # Example
 ///
@@ -69,62 +69,76 @@ use crate::TreeBackend;
 /// ```
 pub struct Smt<B, C>
 where
-    B: TreeBackend,
+    B: TreeBackend<HashOf<C>>,
     C: ExecutionContext,
 {
-    b: B,
+    pub(crate) b: B,
     c: PhantomData<C>,
 }
 
 /// A children tuple of a parent node in the sparse Merkle tree.
 #[derive(
-    Debug,
-    Clone,
-    Copy,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    Ord,
-    Hash,
-    zerocopy::TryFromBytes,
-    zerocopy::IntoBytes,
-    zerocopy::Immutable,
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, BorshSerialize, BorshDeserialize,
 )]
-pub struct SmtChildren {
+pub struct SmtChildren<H = Hash> {
     /// The left child associated with `0` in the key traversal.
-    pub left: Hash,
+    pub left: H,
     /// The right child associated with `1` in the key traversal.
-    pub right: Hash,
+    pub right: H,
 }
 
-impl SmtChildren {
+impl<H: Digest> SmtChildren<H> {
     /// Computes the parent node in a sparse Merkle tree, given the children tuple.
-    pub fn parent<C: ExecutionContext>(&self) -> Hash {
+    pub fn parent<C>(&self) -> H
+    where
+        C: ExecutionContext,
+        C::Hasher: Hasher<Output = H>,
+    {
         <C as ExecutionContext>::Hasher::merge(&self.left, &self.right)
     }
-}
 
-impl AsRef<[u8]> for SmtChildren {
-    fn as_ref(&self) -> &[u8] {
-        self.as_bytes()
+    /// Writes the children as the concatenation of the left and right nodes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [self.left.as_ref(), self.right.as_ref()].concat()
+    }
+
+    /// Reads the children from the concatenation of the left and right nodes.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 2 * H::LEN {
+            return None;
+        }
+
+        let (left, right) = bytes.split_at(H::LEN);
+
+        Some(Self {
+            left: H::from_slice(left)?,
+            right: H::from_slice(right)?,
+        })
     }
 }
 
 /// A postorder traversal Merkle opening proof that opens the data to a Merkle root.
-#[derive(
-    Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, BorshSerialize, BorshDeserialize,
-)]
-pub struct SmtOpening {
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, BorshSerialize, BorshDeserialize)]
+pub struct SmtOpening<H = Hash> {
     /// Preimage of the leaf hash
     pub data: Vec<u8>,
 
     /// Postorder traversal siblings from leaf to root.
-    pub opening: Vec<Hash>,
+    pub opening: Vec<H>,
+}
+
+impl<H> Default for SmtOpening<H> {
+    fn default() -> Self {
+        Self {
+            data: Vec::new(),
+            opening: Vec::new(),
+        }
+    }
 }
 
 impl<B, C> Default for Smt<B, C>
 where
-    B: TreeBackend + Default,
+    B: TreeBackend<HashOf<C>> + Default,
     C: ExecutionContext,
 {
     fn default() -> Self {
@@ -137,7 +151,7 @@ where
 
 impl<B, C> Clone for Smt<B, C>
 where
-    B: TreeBackend + Clone,
+    B: TreeBackend<HashOf<C>> + Clone,
     C: ExecutionContext,
 {
     fn clone(&self) -> Self {
@@ -150,7 +164,7 @@ where
 
 impl<B, C> From<B> for Smt<B, C>
 where
-    B: TreeBackend,
+    B: TreeBackend<HashOf<C>>,
     C: ExecutionContext,
 {
     fn from(b: B) -> Self {
@@ -160,42 +174,118 @@ where
 
 impl<B, C> Smt<B, C>
 where
-    B: TreeBackend,
+    B: TreeBackend<HashOf<C>>,
     C: ExecutionContext,
 {
     /// Returns a stateless empty root to be used for newly allocated sparse Merkle trees.
     ///
     /// This is a cryptographic stateless computation and won't touch the data backend.
-    pub fn empty_tree_root() -> Hash {
-        Hash::default()
+    pub fn empty_tree_root() -> HashOf<C> {
+        HashOf::<C>::zero()
+    }
+
+    /// Returns the underlying data backend.
+    pub fn backend(&self) -> &B {
+        &self.b
     }
 
-    /// Removes an entire subtree along with its linked leaf keys and data.
-    pub fn prune(&mut self, root: &Hash) -> anyhow::Result<()> {
-        // TODO don't recurse here to not overflow the stack on very deep trees
-        if let Some(SmtChildren { left, right }) = self.b.get_children(root)? {
-            self.prune(&left)?;
-            self.prune(&right)?;
+    /// Unwraps the underlying data backend.
+    pub fn into_backend(self) -> B {
+        self.b
+    }
+
+    /// Removes the subtree of the provided root, along with its linked leaf keys and data.
+    ///
+    /// Nodes are content-addressed and shared across roots. Every root returned by [Smt::insert]
+    /// or [Smt::import] is retained until it is pruned, and a node is removed only when it is
+    /// neither retained nor referenced by a parent. Pruning a root never invalidates the openings
+    /// of other retained roots, even if they are part of its subtree. Each retained root should be
+    /// pruned once.
+    pub fn prune(&mut self, root: &HashOf<C>) -> anyhow::Result<()> {
+        // release the retention of the root
+        if *root == HashOf::<C>::zero() || self.unref(root)? > 0 {
+            return Ok(());
+        }
+
+        let mut stack = Vec::from([*root]);
+
+        while let Some(node) = stack.pop() {
+            if node == HashOf::<C>::zero() || self.b.get_node_refs(&node)?.unwrap_or(0) > 0 {
+                continue;
+            }
+
+            if let Some(SmtChildren { left, right }) = self.b.remove_children(&node)? {
+                for child in [left, right] {
+                    if child != HashOf::<C>::zero() && self.unref(&child)? == 0 {
+                        stack.push(child);
+                    }
+                }
+            }
+
+            if let Some(key) = self.b.remove_node_key(&node)? {
+                self.b.remove_key_data(&key)?;
+            }
+
+            self.b.remove_node_refs(&node)?;
         }
 
-        if let Some(key) = self.b.remove_node_key(root)? {
-            self.b.remove_key_data(&key)?;
+        Ok(())
+    }
+
+    /// Links the parent to its children, referencing them if the parent is new to the backend.
+    pub(crate) fn link(
+        &mut self,
+        parent: &HashOf<C>,
+        children: &SmtChildren<HashOf<C>>,
+    ) -> anyhow::Result<()> {
+        if self.b.insert_children(parent, children)? {
+            return Ok(());
         }
 
-        self.b.remove_children(root)?;
+        for child in [children.left, children.right] {
+            if child != HashOf::<C>::zero() {
+                let refs = self.b.get_node_refs(&child)?.unwrap_or(0);
+
+                self.b.insert_node_refs(&child, refs + 1)?;
+            }
+        }
 
         Ok(())
     }
 
+    /// Retains a root until it is pruned, returning it.
+    pub(crate) fn retain(&mut self, root: HashOf<C>) -> anyhow::Result<HashOf<C>> {
+        if root != HashOf::<C>::zero() {
+            let refs = self.b.get_node_refs(&root)?.unwrap_or(0);
+
+            self.b.insert_node_refs(&root, refs + 1)?;
+        }
+
+        Ok(root)
+    }
+
+    /// Drops a reference of the node, returning the remaining count.
+    fn unref(&mut self, node: &HashOf<C>) -> anyhow::Result<u64> {
+        let refs = self.b.get_node_refs(node)?.unwrap_or(0).saturating_sub(1);
+
+        if refs == 0 {
+            self.b.remove_node_refs(node)?;
+        } else {
+            self.b.insert_node_refs(node, refs)?;
+        }
+
+        Ok(refs)
+    }
+
     /// Computes a Merkle opening proof for the provided leaf to the root.
     ///
     /// The leaf is defined by the combination of the context and its data.
     pub fn get_opening(
         &self,
         context: &str,
-        root: Hash,
+        root: HashOf<C>,
         data: &[u8],
-    ) -> anyhow::Result<Option<SmtOpening>> {
+    ) -> anyhow::Result<Option<SmtOpening<HashOf<C>>>> {
         let key = C::Hasher::key(context, data);
         let data = match self.b.get_key_data(&key)? {
             Some(d) => d,
@@ -204,7 +294,7 @@ where
 
         let (mut i, mut j) = (0, 0);
         let mut leaf_node = root;
-        let mut opening = Vec::with_capacity(HASH_LEN * 8);
+        let mut opening = Vec::with_capacity(HashOf::<C>::LEN * 8);
 
         while let Some(SmtChildren { left, right }) = self.b.get_children(&leaf_node)? {
             // is current node a leaf?
@@ -212,7 +302,7 @@ where
                 break;
             }
 
-            let bit = (key[i] >> (7 - j)) & 1;
+            let bit = (key.as_ref()[i] >> (7 - j)) & 1;
 
             if bit == 0 {
                 leaf_node = left;
@@ -224,7 +314,7 @@ where
 
             j += 1;
 
-            if j == 8 && i == HASH_LEN {
+            if j == 8 && i == HashOf::<C>::LEN {
                 break;
             } else if j == 8 {
                 j = 0;
@@ -238,38 +328,48 @@ where
     }
 
     /// Verifies a proof obtained via [Smt::get_opening].
-    pub fn verify(context: &str, root: &Hash, proof: &SmtOpening) -> bool {
+    pub fn verify(context: &str, root: &HashOf<C>, proof: &SmtOpening<HashOf<C>>) -> bool {
+        &Self::compute_root(context, proof) == root
+    }
+
+    /// Computes the Merkle root the opening proof resolves to.
+    ///
+    /// The proof is valid for a root if, and only if, the root equals the computed value.
+    pub fn compute_root(context: &str, proof: &SmtOpening<HashOf<C>>) -> HashOf<C> {
         let key = C::Hasher::key(context, &proof.data);
         let node = C::Hasher::hash(&proof.data);
         let mut depth = proof.opening.len();
 
-        let node = proof.opening.iter().fold(node, |node, sibling| {
+        proof.opening.iter().fold(node, |node, sibling| {
             depth -= 1;
 
             let i = depth / 8;
             let j = depth % 8;
-            let bit = (key[i] >> (7 - j)) & 1;
+            let bit = (key.as_ref()[i] >> (7 - j)) & 1;
 
             if bit == 0 {
                 C::Hasher::merge(&node, sibling)
             } else {
                 C::Hasher::merge(sibling, &node)
             }
-        });
-
-        &node == root
+        })
     }
 
     /// Returns `true` if the provided node is associated with a leaf key.
-    pub fn is_leaf(&self, node: &Hash) -> anyhow::Result<bool> {
-        Ok(node == &Hash::default() || self.b.has_node_key(node)?)
+    pub fn is_leaf(&self, node: &HashOf<C>) -> anyhow::Result<bool> {
+        Ok(node == &HashOf::<C>::zero() || self.b.has_node_key(node)?)
     }
 
     /// Inserts a leaf into the tree.
     ///
     /// The leaf key will be computed given the context and data, and will have a collision
-    /// resistance up to [HASH_LEN] bytes.
-    pub fn insert(&mut self, root: Hash, context: &str, data: Vec<u8>) -> anyhow::Result<Hash> {
+    /// resistance up to [Digest::LEN] bytes. The returned root is retained until it is pruned.
+    pub fn insert(
+        &mut self,
+        root: HashOf<C>,
+        context: &str,
+        data: Vec<u8>,
+    ) -> anyhow::Result<HashOf<C>> {
         let mut depth = 0;
 
         let key = C::Hasher::key(context, &data);
@@ -279,8 +379,8 @@ where
         self.b.insert_node_key(&leaf, &key)?;
 
         // childless node
-        if root == Hash::default() {
-            return Ok(leaf);
+        if root == HashOf::<C>::zero() {
+            return self.retain(leaf);
         }
 
         // single node tree
@@ -293,8 +393,8 @@ where
             let i = depth / 8;
             let j = depth % 8;
 
-            let mut node_bit = (key[i] >> (7 - j)) & 1;
-            let mut sibling_bit = (sibling_key[i] >> (7 - j)) & 1;
+            let mut node_bit = (key.as_ref()[i] >> (7 - j)) & 1;
+            let mut sibling_bit = (sibling_key.as_ref()[i] >> (7 - j)) & 1;
 
             while node_bit == sibling_bit {
                 depth += 1;
@@ -302,8 +402,8 @@ where
                 let i = depth / 8;
                 let j = depth % 8;
 
-                node_bit = (key[i] >> (7 - j)) & 1;
-                sibling_bit = (sibling_key[i] >> (7 - j)) & 1;
+                node_bit = (key.as_ref()[i] >> (7 - j)) & 1;
+                sibling_bit = (sibling_key.as_ref()[i] >> (7 - j)) & 1;
             }
 
             let children = SmtChildren {
@@ -312,16 +412,16 @@ where
             };
             let mut root = children.parent::<C>();
 
-            self.b.insert_children(&root, &children)?;
+            self.link(&root, &children)?;
 
             while depth > 0 {
                 depth -= 1;
 
                 let i = depth / 8;
                 let j = depth % 8;
-                let bit = (key[i] >> (7 - j)) & 1;
+                let bit = (key.as_ref()[i] >> (7 - j)) & 1;
 
-                let sibling = Hash::default();
+                let sibling = HashOf::<C>::zero();
                 let children = SmtChildren {
                     left: if bit == 0 { root } else { sibling },
                     right: if bit == 0 { sibling } else { root },
@@ -329,21 +429,21 @@ where
 
                 root = children.parent::<C>();
 
-                self.b.insert_children(&root, &children)?;
+                self.link(&root, &children)?;
             }
 
-            return Ok(root);
+            return self.retain(root);
         }
 
         let mut node = root;
-        let mut opening = Vec::with_capacity(HASH_LEN * 8);
+        let mut opening = Vec::with_capacity(HashOf::<C>::LEN * 8);
         let mut is_leaf = false;
 
         // traverse until leaf
         while let Some(SmtChildren { left, right }) = self.b.get_children(&node)? {
             let i = depth / 8;
             let j = depth % 8;
-            let bit = (key[i] >> (7 - j)) & 1;
+            let bit = (key.as_ref()[i] >> (7 - j)) & 1;
             let sibling = if bit == 0 { right } else { left };
 
             node = if bit == 0 { left } else { right };
@@ -353,19 +453,19 @@ where
             depth += 1;
 
             // empty leaf override
-            if node == Hash::default() {
+            if node == HashOf::<C>::zero() {
                 let i = depth / 8;
                 let j = depth % 8;
-                let bit = (key[i] >> (7 - j)) & 1;
+                let bit = (key.as_ref()[i] >> (7 - j)) & 1;
 
                 let children = SmtChildren {
-                    left: if bit == 0 { leaf } else { Hash::default() },
-                    right: if bit == 0 { Hash::default() } else { leaf },
+                    left: if bit == 0 { leaf } else { HashOf::<C>::zero() },
+                    right: if bit == 0 { HashOf::<C>::zero() } else { leaf },
                 };
 
                 node = children.parent::<C>();
 
-                self.b.insert_children(&node, &children)?;
+                self.link(&node, &children)?;
 
                 is_leaf = true;
 
@@ -381,8 +481,8 @@ where
                 let i = depth / 8;
                 let j = depth % 8;
 
-                let mut node_bit = (key[i] >> (7 - j)) & 1;
-                let mut sibling_bit = (sibling_key[i] >> (7 - j)) & 1;
+                let mut node_bit = (key.as_ref()[i] >> (7 - j)) & 1;
+                let mut sibling_bit = (sibling_key.as_ref()[i] >> (7 - j)) & 1;
 
                 while node_bit == sibling_bit {
                     depth += 1;
@@ -390,10 +490,10 @@ where
                     let i = depth / 8;
                     let j = depth % 8;
 
-                    node_bit = (key[i] >> (7 - j)) & 1;
-                    sibling_bit = (sibling_key[i] >> (7 - j)) & 1;
+                    node_bit = (key.as_ref()[i] >> (7 - j)) & 1;
+                    sibling_bit = (sibling_key.as_ref()[i] >> (7 - j)) & 1;
 
-                    opening.push(Hash::default());
+                    opening.push(HashOf::<C>::zero());
                 }
 
                 let children = SmtChildren {
@@ -403,7 +503,7 @@ where
 
                 node = children.parent::<C>();
 
-                self.b.insert_children(&node, &children)?;
+                self.link(&node, &children)?;
 
                 is_leaf = true;
 
@@ -419,7 +519,7 @@ where
             let i = depth / 8;
             let j = depth % 8;
 
-            let bit = (key[i] >> (7 - j)) & 1;
+            let bit = (key.as_ref()[i] >> (7 - j)) & 1;
 
             let children = SmtChildren {
                 left: if bit == 0 { node } else { sibling },
@@ -428,9 +528,9 @@ where
 
             node = children.parent::<C>();
 
-            self.b.insert_children(&node, &children)?;
+            self.link(&node, &children)?;
         }
 
-        Ok(node)
+        self.retain(node)
     }
 }
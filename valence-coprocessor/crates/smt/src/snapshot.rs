@@ -0,0 +1,411 @@
+use alloc::vec::Vec;
+use borsh::{
+    io::{Read, Write},
+    BorshDeserialize, BorshSerialize,
+};
+use hashbrown::HashSet;
+use valence_coprocessor_core::{Digest, ExecutionContext, Hash, HashOf, Hasher};
+
+use crate::{Smt, SmtChildren, TreeBackend};
+
+/// A record of a portable sparse Merkle tree snapshot stream.
+///
+/// A stream starts with a [SmtSnapshotRecord::Header], followed by the tree records in postorder
+/// (children always precede their parent), and is closed by a [SmtSnapshotRecord::End] that
+/// carries the checksum of all the preceding records.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum SmtSnapshotRecord<H = Hash> {
+    /// Opens the stream, identifying the format and the exported root.
+    Header {
+        /// Must be [SmtSnapshotRecord::MAGIC].
+        magic: [u8; 4],
+        /// Format version of the stream.
+        version: u16,
+        /// The exported Merkle root.
+        root: H,
+    },
+
+    /// A parent node to children relationship.
+    Children {
+        /// The parent node.
+        parent: H,
+        /// The children of the parent node.
+        children: SmtChildren<H>,
+    },
+
+    /// A leaf node to leaf key relationship.
+    NodeKey {
+        /// The leaf node.
+        node: H,
+        /// The leaf key associated with the node.
+        key: H,
+    },
+
+    /// A leaf key to leaf data relationship. Always follows the [SmtSnapshotRecord::NodeKey] of
+    /// the same key.
+    KeyData {
+        /// The leaf key.
+        key: H,
+        /// The leaf data.
+        data: Vec<u8>,
+    },
+
+    /// Closes the stream.
+    End {
+        /// Checksum of all the preceding records.
+        checksum: H,
+    },
+}
+
+impl<H: Digest> SmtSnapshotRecord<H> {
+    /// Magic bytes that identify a snapshot stream.
+    pub const MAGIC: [u8; 4] = *b"vsmt";
+
+    /// Current version of the snapshot stream format.
+    pub const VERSION: u16 = 1;
+
+    /// Writes the record, folding its bytes into the running checksum.
+    fn write<C, W>(&self, writer: &mut W, checksum: &mut H) -> anyhow::Result<()>
+    where
+        C: ExecutionContext,
+        C::Hasher: Hasher<Output = H>,
+        W: Write,
+    {
+        let bytes = borsh::to_vec(self)
+            .map_err(|e| anyhow::anyhow!("failed to serialize snapshot record: {e}"))?;
+
+        *checksum = C::Hasher::merge(checksum, &C::Hasher::hash(&bytes));
+
+        writer
+            .write_all(&bytes)
+            .map_err(|e| anyhow::anyhow!("failed to write snapshot record: {e}"))
+    }
+
+    /// Reads a record, folding its bytes into the running checksum.
+    fn read<C, R>(reader: &mut R, checksum: &mut H) -> anyhow::Result<Self>
+    where
+        C: ExecutionContext,
+        C::Hasher: Hasher<Output = H>,
+        R: Read,
+    {
+        let record = Self::deserialize_reader(reader)
+            .map_err(|e| anyhow::anyhow!("failed to read snapshot record: {e}"))?;
+
+        let bytes = borsh::to_vec(&record)
+            .map_err(|e| anyhow::anyhow!("failed to serialize snapshot record: {e}"))?;
+
+        *checksum = C::Hasher::merge(checksum, &C::Hasher::hash(&bytes));
+
+        Ok(record)
+    }
+}
+
+impl<B, C> Smt<B, C>
+where
+    B: TreeBackend<HashOf<C>>,
+    C: ExecutionContext,
+{
+    /// Writes the subtree of the provided root to a portable snapshot stream.
+    ///
+    /// The stream is backend agnostic, and can be loaded into any other backend via
+    /// [Smt::import], provided both trees share the same [ExecutionContext].
+    pub fn export<W: Write>(&self, root: &HashOf<C>, writer: &mut W) -> anyhow::Result<()> {
+        let mut checksum = HashOf::<C>::zero();
+
+        SmtSnapshotRecord::Header {
+            magic: SmtSnapshotRecord::<HashOf<C>>::MAGIC,
+            version: SmtSnapshotRecord::<HashOf<C>>::VERSION,
+            root: *root,
+        }
+        .write::<C, _>(writer, &mut checksum)?;
+
+        let mut visited = HashSet::new();
+        let mut stack = Vec::new();
+
+        if root != &HashOf::<C>::zero() {
+            stack.push((*root, false));
+        }
+
+        // iterative postorder so deep trees won't overflow the stack
+        while let Some((node, expanded)) = stack.pop() {
+            if expanded {
+                let children = self.b.get_children(&node)?.ok_or_else(|| {
+                    anyhow::anyhow!("inconsistent tree state; node {node:x?} lost its children")
+                })?;
+
+                SmtSnapshotRecord::Children {
+                    parent: node,
+                    children,
+                }
+                .write::<C, _>(writer, &mut checksum)?;
+
+                continue;
+            }
+
+            if !visited.insert(node) {
+                continue;
+            }
+
+            if let Some(SmtChildren { left, right }) = self.b.get_children(&node)? {
+                stack.push((node, true));
+
+                for child in [right, left] {
+                    if child != HashOf::<C>::zero() {
+                        stack.push((child, false));
+                    }
+                }
+
+                continue;
+            }
+
+            let key = match self.b.get_node_key(&node)? {
+                Some(k) => k,
+                None => anyhow::bail!(
+                    "inconsistent tree state; node {node:x?} is neither a parent nor a leaf"
+                ),
+            };
+
+            let data = match self.b.get_key_data(&key)? {
+                Some(d) => d,
+                None => anyhow::bail!(
+                    "inconsistent tree state; leaf key {key:x?} doesn't have associated data"
+                ),
+            };
+
+            SmtSnapshotRecord::NodeKey { node, key }.write::<C, _>(writer, &mut checksum)?;
+            SmtSnapshotRecord::KeyData { key, data }.write::<C, _>(writer, &mut checksum)?;
+        }
+
+        SmtSnapshotRecord::End { checksum }.write::<C, _>(writer, &mut HashOf::<C>::zero())?;
+
+        Ok(())
+    }
+
+    /// Loads a snapshot stream produced by [Smt::export], returning its root retained until it is
+    /// pruned.
+    ///
+    /// Every parent node is checked against the hash of its children, and every leaf node against
+    /// the hash of its data. Children must be either empty or known to the backend when their
+    /// parent is imported.
+    ///
+    /// The records are persisted as they are validated; a failed import might leave the records
+    /// that preceded the failure in the backend.
+    pub fn import<R: Read>(&mut self, reader: &mut R) -> anyhow::Result<HashOf<C>> {
+        let mut checksum = HashOf::<C>::zero();
+
+        let root = match SmtSnapshotRecord::<HashOf<C>>::read::<C, _>(reader, &mut checksum)? {
+            SmtSnapshotRecord::Header {
+                magic,
+                version,
+                root,
+            } => {
+                anyhow::ensure!(
+                    magic == SmtSnapshotRecord::<HashOf<C>>::MAGIC,
+                    "invalid snapshot magic {magic:x?}"
+                );
+                anyhow::ensure!(
+                    version == SmtSnapshotRecord::<HashOf<C>>::VERSION,
+                    "unsupported snapshot version {version}"
+                );
+
+                root
+            }
+            _ => anyhow::bail!("the snapshot stream doesn't start with a header"),
+        };
+
+        let mut pending = None;
+
+        loop {
+            let expected = checksum;
+            let record = SmtSnapshotRecord::<HashOf<C>>::read::<C, _>(reader, &mut checksum)?;
+
+            if !matches!(record, SmtSnapshotRecord::KeyData { .. }) {
+                anyhow::ensure!(
+                    pending.is_none(),
+                    "the snapshot leaf key {:x?} isn't followed by its data",
+                    pending.map(|(_, k)| k).unwrap_or_else(HashOf::<C>::zero)
+                );
+            }
+
+            match record {
+                SmtSnapshotRecord::Header { .. } => {
+                    anyhow::bail!("unexpected header in the middle of the snapshot stream")
+                }
+
+                SmtSnapshotRecord::Children { parent, children } => {
+                    anyhow::ensure!(
+                        parent == children.parent::<C>(),
+                        "the snapshot node {parent:x?} doesn't match the hash of its children"
+                    );
+
+                    for child in [children.left, children.right] {
+                        anyhow::ensure!(
+                            child == HashOf::<C>::zero() || self.contains(&child)?,
+                            "the snapshot node {parent:x?} references unknown child {child:x?}"
+                        );
+                    }
+
+                    self.link(&parent, &children)?;
+                }
+
+                SmtSnapshotRecord::NodeKey { node, key } => {
+                    pending = Some((node, key));
+                }
+
+                SmtSnapshotRecord::KeyData { key, data } => {
+                    let node = match pending.take() {
+                        Some((node, k)) if k == key => node,
+                        _ => anyhow::bail!(
+                            "the snapshot leaf data of {key:x?} doesn't follow its node key"
+                        ),
+                    };
+
+                    anyhow::ensure!(
+                        node == C::Hasher::hash(&data),
+                        "the snapshot leaf {node:x?} doesn't match the hash of its data"
+                    );
+
+                    self.b.insert_node_key(&node, &key)?;
+                    self.b.insert_key_data(&key, data)?;
+                }
+
+                SmtSnapshotRecord::End { checksum } => {
+                    anyhow::ensure!(checksum == expected, "snapshot checksum mismatch");
+                    anyhow::ensure!(
+                        root == HashOf::<C>::zero() || self.contains(&root)?,
+                        "the snapshot root {root:x?} isn't part of the stream"
+                    );
+
+                    return self.retain(root);
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the node is either a parent or a leaf in the backend.
+    fn contains(&self, node: &HashOf<C>) -> anyhow::Result<bool> {
+        Ok(self.b.get_children(node)?.is_some() || self.b.has_node_key(node)?)
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use alloc::vec;
+    use valence_coprocessor_core::Blake3Context;
+
+    use crate::MemorySmt;
+
+    use super::*;
+
+    fn sample() -> anyhow::Result<(MemorySmt, Hash)> {
+        let context = "poem";
+        let mut tree = MemorySmt::default();
+        let mut root = MemorySmt::empty_tree_root();
+
+        for i in 0..20u8 {
+            root = tree.insert(root, context, vec![0x00, 0x00, i])?;
+        }
+
+        Ok((tree, root))
+    }
+
+    #[test]
+    fn export_import_round_trip() -> anyhow::Result<()> {
+        let (tree, root) = sample()?;
+
+        let mut stream = Vec::new();
+        tree.export(&root, &mut stream)?;
+
+        let mut imported = MemorySmt::default();
+        let imported_root = imported.import(&mut stream.as_slice())?;
+
+        assert_eq!(root, imported_root);
+
+        for i in 0..20u8 {
+            let data = [0x00, 0x00, i];
+            let proof = imported.get_opening("poem", root, &data)?.unwrap();
+
+            assert!(MemorySmt::verify("poem", &root, &proof));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn export_import_empty_tree() -> anyhow::Result<()> {
+        let tree = MemorySmt::default();
+        let root = MemorySmt::empty_tree_root();
+
+        let mut stream = Vec::new();
+        tree.export(&root, &mut stream)?;
+
+        assert_eq!(root, MemorySmt::default().import(&mut stream.as_slice())?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn import_rejects_tampered_stream() -> anyhow::Result<()> {
+        let (tree, root) = sample()?;
+
+        let mut stream = Vec::new();
+        tree.export(&root, &mut stream)?;
+
+        // flip a bit of the first record after the header
+        let header = borsh::to_vec(&SmtSnapshotRecord::Header {
+            magic: SmtSnapshotRecord::<Hash>::MAGIC,
+            version: SmtSnapshotRecord::<Hash>::VERSION,
+            root,
+        })?;
+
+        stream[header.len() + 1] ^= 1;
+
+        assert!(MemorySmt::default().import(&mut stream.as_slice()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn import_rejects_bad_checksum() -> anyhow::Result<()> {
+        let (tree, root) = sample()?;
+
+        let mut stream = Vec::new();
+        tree.export(&root, &mut stream)?;
+
+        let last = stream.len() - 1;
+        stream[last] ^= 1;
+
+        assert!(MemorySmt::default().import(&mut stream.as_slice()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn import_rejects_forged_parent() -> anyhow::Result<()> {
+        let mut checksum = Hash::default();
+        let mut stream = Vec::new();
+
+        let children = SmtChildren {
+            left: Hash::default(),
+            right: Hash::default(),
+        };
+        let parent = [0xff; 32];
+
+        SmtSnapshotRecord::Header {
+            magic: SmtSnapshotRecord::<Hash>::MAGIC,
+            version: SmtSnapshotRecord::<Hash>::VERSION,
+            root: parent,
+        }
+        .write::<Blake3Context, _>(&mut stream, &mut checksum)?;
+
+        SmtSnapshotRecord::Children { parent, children }
+            .write::<Blake3Context, _>(&mut stream, &mut checksum)?;
+
+        SmtSnapshotRecord::End { checksum }
+            .write::<Blake3Context, _>(&mut stream, &mut Hash::default())?;
+
+        assert!(MemorySmt::default().import(&mut stream.as_slice()).is_err());
+
+        Ok(())
+    }
+}
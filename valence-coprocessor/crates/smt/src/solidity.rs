@@ -0,0 +1,225 @@
+use alloc::{format, string::String};
+use valence_coprocessor_core::{Keccak256Hasher, HASH_LEN};
+
+/// Generates a Solidity library that verifies a [crate::SmtOpening] produced under the
+/// [valence_coprocessor_core::KeccakContext].
+///
+/// The library mirrors [crate::Smt::verify], so contracts can check openings of a published root
+/// without a ZK proof. The checked-in `contracts/src/SmtVerifier.sol` is the output of this
+/// function.
+pub fn solidity_verifier() -> String {
+    let data_prefix = Keccak256Hasher::DATA_PREFIX[0];
+    let merge_prefix = Keccak256Hasher::MERGE_PREFIX[0];
+    let key_prefix = Keccak256Hasher::KEY_PREFIX[0];
+    let max_depth = HASH_LEN * 8;
+
+    format!(
+        r#"// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.20;
+
+/// @title SmtVerifier
+/// @notice Verifies openings of a Valence sparse Merkle tree computed under the keccak256
+/// execution context.
+/// @dev Generated by `valence_smt::solidity_verifier`; do not edit by hand.
+library SmtVerifier {{
+    bytes1 internal constant DATA_PREFIX = {data_prefix:#04x};
+    bytes1 internal constant MERGE_PREFIX = {merge_prefix:#04x};
+    bytes1 internal constant KEY_PREFIX = {key_prefix:#04x};
+    uint256 internal constant MAX_DEPTH = {max_depth};
+
+    /// @notice Computes the leaf key of the data under the provided context.
+    function key(string memory context, bytes memory data) internal pure returns (bytes32) {{
+        return keccak256(abi.encodePacked(KEY_PREFIX, uint64(bytes(context).length), context, data));
+    }}
+
+    /// @notice Computes the leaf node of the data.
+    function hash(bytes memory data) internal pure returns (bytes32) {{
+        return keccak256(abi.encodePacked(DATA_PREFIX, data));
+    }}
+
+    /// @notice Computes the parent node of the provided children.
+    function merge(bytes32 left, bytes32 right) internal pure returns (bytes32) {{
+        return keccak256(abi.encodePacked(MERGE_PREFIX, left, right));
+    }}
+
+    /// @notice Verifies that the data opens to the root under the provided context.
+    /// @param opening The siblings from the leaf to the root, as in `SmtOpening::opening`.
+    function verify(string memory context, bytes32 root, bytes memory data, bytes32[] memory opening)
+        internal
+        pure
+        returns (bool)
+    {{
+        if (opening.length > MAX_DEPTH) {{
+            return false;
+        }}
+
+        uint256 k = uint256(key(context, data));
+        bytes32 node = hash(data);
+        uint256 depth = opening.length;
+
+        for (uint256 i = 0; i < opening.length; i++) {{
+            depth -= 1;
+
+            // the key bits select the path from the root, most significant bit first
+            if ((k >> (MAX_DEPTH - 1 - depth)) & 1 == 0) {{
+                node = merge(node, opening[i]);
+            }} else {{
+                node = merge(opening[i], node);
+            }}
+        }}
+
+        return node == root;
+    }}
+}}
+"#
+    )
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use alloc::vec::Vec;
+    use tiny_keccak::{Hasher as _, Keccak};
+    use valence_coprocessor_core::{Hash, Hasher, KeccakContext};
+
+    use crate::{MemoryBackend, Smt};
+
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    /// Generates the opening the forge tests of `contracts/test` check the verifier against.
+    fn solidity_fixture() -> anyhow::Result<String> {
+        let context = "poem";
+        let data = [0x00, 0x00, 0x07];
+        let mut tree = Smt::<MemoryBackend, KeccakContext>::default();
+        let mut root = Smt::<MemoryBackend, KeccakContext>::empty_tree_root();
+
+        for i in 0..20u8 {
+            root = tree.insert(root, context, alloc::vec![0x00, 0x00, i])?;
+        }
+
+        let proof = tree.get_opening(context, root, &data)?.unwrap();
+        let count = proof.opening.len();
+        let siblings: String = proof
+            .opening
+            .iter()
+            .enumerate()
+            .map(|(i, s)| format!("        siblings[{i}] = 0x{};\n", hex(s)))
+            .collect();
+        let root = hex(&root);
+        let data = hex(&data);
+
+        Ok(format!(
+            r#"// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.20;
+
+/// @title SmtVerifierFixture
+/// @notice An opening of a sparse Merkle tree computed by `valence_smt` under the keccak256
+/// execution context.
+/// @dev Generated by the `solidity` tests of `valence_smt`; do not edit by hand.
+library SmtVerifierFixture {{
+    string internal constant CONTEXT = "{context}";
+    bytes32 internal constant ROOT = 0x{root};
+
+    function data() internal pure returns (bytes memory) {{
+        return hex"{data}";
+    }}
+
+    function opening() internal pure returns (bytes32[] memory siblings) {{
+        siblings = new bytes32[]({count});
+{siblings}    }}
+}}
+"#
+        ))
+    }
+
+    fn keccak256(data: &[u8]) -> Hash {
+        let mut hash = Hash::default();
+        let mut hasher = Keccak::v256();
+
+        hasher.update(data);
+        hasher.finalize(&mut hash);
+
+        hash
+    }
+
+    #[test]
+    fn checked_in_verifier_is_up_to_date() {
+        let checked_in = include_str!("../../../contracts/src/SmtVerifier.sol");
+
+        assert_eq!(
+            checked_in,
+            solidity_verifier(),
+            "contracts/src/SmtVerifier.sol is stale; regenerate it with `solidity_verifier`"
+        );
+    }
+
+    #[test]
+    fn checked_in_fixture_is_up_to_date() -> anyhow::Result<()> {
+        let checked_in = include_str!("../../../contracts/test/SmtVerifierFixture.sol");
+
+        assert_eq!(
+            checked_in,
+            solidity_fixture()?,
+            "contracts/test/SmtVerifierFixture.sol is stale; regenerate it with `solidity_fixture`"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn hasher_matches_abi_encode_packed() {
+        let context = "poem";
+        let data = b"the quick brown fox";
+
+        // abi.encodePacked(KEY_PREFIX, uint64(bytes(context).length), context, data)
+        let mut packed = Vec::new();
+
+        packed.extend_from_slice(Keccak256Hasher::KEY_PREFIX);
+        packed.extend_from_slice(&(context.len() as u64).to_be_bytes());
+        packed.extend_from_slice(context.as_bytes());
+        packed.extend_from_slice(data);
+
+        assert_eq!(Keccak256Hasher::key(context, data), keccak256(&packed));
+
+        let mut packed = Vec::new();
+
+        packed.extend_from_slice(Keccak256Hasher::DATA_PREFIX);
+        packed.extend_from_slice(data);
+
+        assert_eq!(Keccak256Hasher::hash(data), keccak256(&packed));
+
+        let a = [0xaa; 32];
+        let b = [0xbb; 32];
+        let mut packed = Vec::new();
+
+        packed.extend_from_slice(Keccak256Hasher::MERGE_PREFIX);
+        packed.extend_from_slice(&a);
+        packed.extend_from_slice(&b);
+
+        assert_eq!(Keccak256Hasher::merge(&a, &b), keccak256(&packed));
+    }
+
+    #[test]
+    fn keccak_openings_verify() -> anyhow::Result<()> {
+        let context = "poem";
+        let mut tree = Smt::<MemoryBackend, KeccakContext>::default();
+        let mut root = Smt::<MemoryBackend, KeccakContext>::empty_tree_root();
+
+        for i in 0..20u8 {
+            root = tree.insert(root, context, alloc::vec![0x00, 0x00, i])?;
+        }
+
+        for i in 0..20u8 {
+            let proof = tree.get_opening(context, root, &[0x00, 0x00, i])?.unwrap();
+
+            assert!(Smt::<MemoryBackend, KeccakContext>::verify(
+                context, &root, &proof
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -1,12 +1,54 @@
 use proptest::collection;
 use proptest::prelude::*;
-use valence_coprocessor_core::ExecutionContext;
+use valence_coprocessor_core::{ExecutionContext, HashOf, Hasher};
 
 use crate::{Smt, TreeBackend};
 
+/// A blake3 hasher with 64 bytes outputs, read from its extendable output.
+struct WideHasher;
+
+/// An execution context with nodes twice as wide as the default [valence_coprocessor_core::Hash].
+struct WideContext;
+
+impl WideHasher {
+    fn digest(parts: &[&[u8]]) -> [u8; 64] {
+        let mut hasher = ::blake3::Hasher::new();
+        let mut output = [0u8; 64];
+
+        parts.iter().for_each(|p| {
+            hasher.update(p);
+        });
+        hasher.finalize_xof().fill(&mut output);
+
+        output
+    }
+}
+
+impl Hasher for WideHasher {
+    type Output = [u8; 64];
+
+    fn key(context: &str, data: &[u8]) -> [u8; 64] {
+        let len = (context.len() as u64).to_be_bytes();
+
+        Self::digest(&[&[0x02], &len, context.as_bytes(), data])
+    }
+
+    fn hash(data: &[u8]) -> [u8; 64] {
+        Self::digest(&[&[0x00], data])
+    }
+
+    fn merge(a: &[u8; 64], b: &[u8; 64]) -> [u8; 64] {
+        Self::digest(&[&[0x01], a, b])
+    }
+}
+
+impl ExecutionContext for WideContext {
+    type Hasher = WideHasher;
+}
+
 fn property_check<B, C>(mut tree: Smt<B, C>, numbers: Vec<u32>)
 where
-    B: TreeBackend,
+    B: TreeBackend<HashOf<C>>,
     C: ExecutionContext,
 {
     let context = "property";
@@ -33,6 +75,78 @@ where
     }
 }
 
+fn export_import_check<B, C, D>(mut tree: Smt<B, C>, mut target: Smt<D, C>, numbers: Vec<u32>)
+where
+    B: TreeBackend<HashOf<C>>,
+    C: ExecutionContext,
+    D: TreeBackend<HashOf<C>>,
+{
+    let context = "property";
+    let mut root = Smt::<B, C>::empty_tree_root();
+
+    for n in &numbers {
+        root = tree
+            .insert(root, context, n.to_le_bytes().to_vec())
+            .unwrap();
+    }
+
+    let mut stream = Vec::new();
+
+    tree.export(&root, &mut stream).unwrap();
+
+    assert_eq!(root, target.import(&mut stream.as_slice()).unwrap());
+
+    for n in numbers {
+        let data = n.to_le_bytes();
+        let proof = target.get_opening(context, root, &data).unwrap().unwrap();
+
+        assert!(Smt::<D, C>::verify(context, &root, &proof));
+        assert_eq!(&data, proof.data.as_slice());
+    }
+}
+
+fn prune_check<B, C>(mut tree: Smt<B, C>, numbers: Vec<u32>)
+where
+    B: TreeBackend<HashOf<C>>,
+    C: ExecutionContext,
+{
+    let context = "property";
+    let mut roots = vec![Smt::<B, C>::empty_tree_root()];
+
+    for n in &numbers {
+        let root = *roots.last().unwrap();
+        let root = tree
+            .insert(root, context, n.to_le_bytes().to_vec())
+            .unwrap();
+
+        roots.push(root);
+    }
+
+    // a newer root might reference an older one, as with a single leaf tree
+    let root = roots.pop().unwrap();
+    let newer = tree.insert(root, context, b"newer".to_vec()).unwrap();
+
+    tree.prune(&newer).unwrap();
+
+    for (r, n) in roots.iter().skip(1).zip(&numbers) {
+        let data = n.to_le_bytes();
+        let proof = tree.get_opening(context, *r, &data).unwrap().unwrap();
+
+        assert!(Smt::<B, C>::verify(context, r, &proof));
+
+        tree.prune(r).unwrap();
+    }
+
+    for n in numbers {
+        let data = n.to_le_bytes();
+        let proof = tree.get_opening(context, root, &data).unwrap().unwrap();
+
+        assert!(Smt::<B, C>::verify(context, &root, &proof));
+    }
+
+    assert!(tree.get_opening(context, root, b"newer").unwrap().is_none());
+}
+
 proptest! {
     #[test]
     #[cfg(feature = "memory")]
@@ -49,4 +163,96 @@ proptest! {
 
         property_check(smt, numbers);
     }
+
+    #[test]
+    #[cfg(feature = "memory")]
+    fn memory_prune_check(numbers in collection::vec(0u32..u32::MAX, 1..100)) {
+        prune_check(crate::MemorySmt::default(), numbers);
+    }
+
+    #[test]
+    #[cfg(feature = "rocksdb")]
+    fn rocksdb_prune_check(numbers in collection::vec(0u32..u32::MAX, 1..100)) {
+        let path = ::tempfile::tempdir().unwrap();
+        let backend = crate::RocksBackend::open(path).unwrap();
+        let smt: Smt<_, valence_coprocessor_core::Blake3Context> = Smt::from(backend);
+
+        prune_check(smt, numbers);
+    }
+
+    #[test]
+    #[cfg(feature = "memory")]
+    fn memory_export_import_check(numbers in collection::vec(0u32..u32::MAX, 1..100)) {
+        export_import_check(crate::MemorySmt::default(), crate::MemorySmt::default(), numbers);
+    }
+
+    #[test]
+    #[cfg(all(feature = "memory", feature = "rocksdb"))]
+    fn memory_to_rocksdb_export_import_check(numbers in collection::vec(0u32..u32::MAX, 1..100)) {
+        let path = ::tempfile::tempdir().unwrap();
+        let backend = crate::RocksBackend::open(path).unwrap();
+        let smt: Smt<_, valence_coprocessor_core::Blake3Context> = Smt::from(backend);
+
+        export_import_check(crate::MemorySmt::default(), smt, numbers);
+    }
+
+    #[test]
+    #[cfg(all(feature = "memory", feature = "sha3"))]
+    fn memory_sha3_property_check(numbers in collection::vec(0u32..u32::MAX, 1..100)) {
+        let smt: Smt<_, valence_coprocessor_core::Sha3Context> =
+            Smt::from(crate::MemoryBackend::default());
+
+        property_check(smt, numbers);
+    }
+
+    #[test]
+    #[cfg(all(feature = "memory", feature = "sha3"))]
+    fn memory_sha3_export_import_check(numbers in collection::vec(0u32..u32::MAX, 1..100)) {
+        let smt: Smt<_, valence_coprocessor_core::Sha3Context> =
+            Smt::from(crate::MemoryBackend::default());
+        let target: Smt<_, valence_coprocessor_core::Sha3Context> =
+            Smt::from(crate::MemoryBackend::default());
+
+        export_import_check(smt, target, numbers);
+    }
+
+    #[test]
+    #[cfg(all(feature = "memory", feature = "poseidon"))]
+    fn memory_poseidon_property_check(numbers in collection::vec(0u32..u32::MAX, 1..10)) {
+        let smt: Smt<_, valence_coprocessor_core::PoseidonContext> =
+            Smt::from(crate::MemoryBackend::default());
+
+        property_check(smt, numbers);
+    }
+
+    #[test]
+    #[cfg(all(feature = "memory", feature = "sha2"))]
+    fn memory_sha256_property_check(numbers in collection::vec(0u32..u32::MAX, 1..100)) {
+        property_check(crate::Sha256Smt::default(), numbers);
+    }
+
+    #[test]
+    #[cfg(feature = "memory")]
+    fn memory_wide_property_check(numbers in collection::vec(0u32..u32::MAX, 1..100)) {
+        let smt: Smt<crate::MemoryBackend<[u8; 64]>, WideContext> = Smt::default();
+
+        property_check(smt, numbers);
+    }
+
+    #[test]
+    #[cfg(feature = "memory")]
+    fn memory_wide_export_import_check(numbers in collection::vec(0u32..u32::MAX, 1..100)) {
+        let smt: Smt<crate::MemoryBackend<[u8; 64]>, WideContext> = Smt::default();
+        let target: Smt<crate::MemoryBackend<[u8; 64]>, WideContext> = Smt::default();
+
+        export_import_check(smt, target, numbers);
+    }
+
+    #[test]
+    #[cfg(feature = "memory")]
+    fn memory_wide_prune_check(numbers in collection::vec(0u32..u32::MAX, 1..100)) {
+        let smt: Smt<crate::MemoryBackend<[u8; 64]>, WideContext> = Smt::default();
+
+        prune_check(smt, numbers);
+    }
 }
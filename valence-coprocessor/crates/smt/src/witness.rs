@@ -0,0 +1,846 @@
+use core::cell::RefCell;
+
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+use hashbrown::{HashMap, HashSet};
+use valence_coprocessor_core::{Digest, ExecutionContext, Hash, HashOf, Hasher};
+
+use crate::{Smt, SmtChildren, TreeBackend};
+
+/// The minimal set of tree relationships required to replay a sequence of operations.
+///
+/// It is the multi-opening of every path touched during a dry-run, as captured by
+/// [RecordingBackend]. Parent nodes are not part of the witness; they are recomputed from their
+/// children when the witness is loaded, binding every node to its hash.
+///
+/// The absent relationships observed during the dry-run are also part of the witness, so a
+/// replay can tell a node known to be absent apart from a node that is outside the witness.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, BorshSerialize, BorshDeserialize)]
+pub struct SmtWitness<H = Hash> {
+    /// Children of the touched parent nodes.
+    pub children: Vec<SmtChildren<H>>,
+
+    /// Touched leaf node to leaf key relationships.
+    pub node_keys: Vec<(H, H)>,
+
+    /// Touched leaf key to leaf data relationships, including the data of every touched leaf node.
+    pub key_data: Vec<(H, Vec<u8>)>,
+
+    /// Touched nodes without children.
+    pub childless: Vec<H>,
+
+    /// Touched nodes without a leaf key.
+    pub keyless: Vec<H>,
+
+    /// Touched leaf keys without data.
+    pub dataless: Vec<H>,
+}
+
+impl<H> Default for SmtWitness<H> {
+    fn default() -> Self {
+        Self {
+            children: Vec::new(),
+            node_keys: Vec::new(),
+            key_data: Vec::new(),
+            childless: Vec::new(),
+            keyless: Vec::new(),
+            dataless: Vec::new(),
+        }
+    }
+}
+
+impl<H: Digest> SmtWitness<H> {
+    /// Returns `true` if no relationship was captured.
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+            && self.node_keys.is_empty()
+            && self.key_data.is_empty()
+            && self.childless.is_empty()
+            && self.keyless.is_empty()
+            && self.dataless.is_empty()
+    }
+
+    /// Writes the witness relationships into the provided backend, recomputing the parent nodes
+    /// with the hasher of the execution context.
+    ///
+    /// Every leaf node must come with the data of its leaf key, and match the hash of that data.
+    /// The absent relationships must not contradict the recomputed parents nor the present
+    /// relationships; otherwise, a forged witness could turn a parent into a leaf and steer the
+    /// replay to another root.
+    pub fn load<B, C>(&self, backend: &mut B) -> anyhow::Result<()>
+    where
+        B: TreeBackend<H>,
+        C: ExecutionContext,
+        C::Hasher: Hasher<Output = H>,
+    {
+        let data: HashMap<_, _> = self.key_data.iter().map(|(k, d)| (k, d)).collect();
+        let leaves: HashMap<_, _> = self.node_keys.iter().map(|(n, k)| (n, k)).collect();
+        let parents: HashMap<_, _> = self.children.iter().map(|c| (c.parent::<C>(), c)).collect();
+
+        anyhow::ensure!(
+            data.len() == self.key_data.len(),
+            "the witness holds a leaf key with more than one data"
+        );
+        anyhow::ensure!(
+            leaves.len() == self.node_keys.len(),
+            "the witness holds a leaf node with more than one key"
+        );
+
+        for (node, key) in &self.node_keys {
+            let d = match data.get(key) {
+                Some(d) => d,
+                None => anyhow::bail!("the witness leaf {node:x?} doesn't carry its data"),
+            };
+
+            anyhow::ensure!(
+                node == &C::Hasher::hash(d),
+                "the witness leaf {node:x?} doesn't match the hash of its data"
+            );
+            anyhow::ensure!(
+                !parents.contains_key(node),
+                "the witness node {node:x?} is both a parent and a leaf"
+            );
+        }
+
+        for node in &self.childless {
+            anyhow::ensure!(
+                !parents.contains_key(node),
+                "the witness parent {node:x?} is also claimed to have no children"
+            );
+        }
+
+        for node in &self.keyless {
+            anyhow::ensure!(
+                !leaves.contains_key(node),
+                "the witness leaf {node:x?} is also claimed to have no key"
+            );
+        }
+
+        for key in &self.dataless {
+            anyhow::ensure!(
+                !data.contains_key(key),
+                "the witness leaf key {key:x?} is also claimed to have no data"
+            );
+        }
+
+        for (parent, children) in &parents {
+            backend.insert_children(parent, children)?;
+        }
+
+        for (node, key) in &self.node_keys {
+            backend.insert_node_key(node, key)?;
+        }
+
+        for (key, data) in &self.key_data {
+            backend.insert_key_data(key, data.clone())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Recording<H> {
+    children: HashMap<H, SmtChildren<H>>,
+    node_keys: HashMap<H, H>,
+    key_data: HashMap<H, Vec<u8>>,
+    childless: HashSet<H>,
+    keyless: HashSet<H>,
+    dataless: HashSet<H>,
+    children_written: HashSet<H>,
+    node_keys_written: HashSet<H>,
+    key_data_written: HashSet<H>,
+}
+
+impl<H> Default for Recording<H> {
+    fn default() -> Self {
+        Self {
+            children: HashMap::new(),
+            node_keys: HashMap::new(),
+            key_data: HashMap::new(),
+            childless: HashSet::new(),
+            keyless: HashSet::new(),
+            dataless: HashSet::new(),
+            children_written: HashSet::new(),
+            node_keys_written: HashSet::new(),
+            key_data_written: HashSet::new(),
+        }
+    }
+}
+
+impl<H: Digest> Recording<H> {
+    fn children(&mut self, parent: &H, children: Option<SmtChildren<H>>) {
+        if parent == &H::zero() || self.children_written.contains(parent) {
+            return;
+        }
+
+        match children {
+            Some(c) => {
+                self.children.entry(*parent).or_insert(c);
+            }
+            None => {
+                self.childless.insert(*parent);
+            }
+        }
+    }
+
+    fn node_key(&mut self, node: &H, key: Option<H>) {
+        if node == &H::zero() || self.node_keys_written.contains(node) {
+            return;
+        }
+
+        match key {
+            Some(k) => {
+                self.node_keys.entry(*node).or_insert(k);
+            }
+            None => {
+                self.keyless.insert(*node);
+            }
+        }
+    }
+
+    fn key_data(&mut self, key: &H, data: Option<&Vec<u8>>) {
+        if self.key_data_written.contains(key) {
+            return;
+        }
+
+        match data {
+            Some(d) => {
+                self.key_data.entry(*key).or_insert_with(|| d.clone());
+            }
+            None => {
+                self.dataless.insert(*key);
+            }
+        }
+    }
+}
+
+/// A [TreeBackend] wrapper that captures the [SmtWitness] of the operations performed on it.
+///
+/// The host performs a dry-run of the same tree operations a guest program is about to execute;
+/// every relationship read from the inner backend is recorded, unless it was written earlier in
+/// the same run, since the guest will compute it by itself. Writes are forwarded to the inner
+/// backend.
+///
+/// The guest program replays the operations against a backend loaded from the witness, which is
+/// guaranteed to produce the same results as the dry-run. Node reference counts are host-side
+/// bookkeeping that don't affect the tree roots, so they are not captured.
+///
+/// # Example
+///
+/// ```rust
+/// #[cfg(feature = "memory")]
+/// fn run() -> anyhow::Result<()> {
+///     use valence_coprocessor_core::Blake3Context;
+///     use valence_smt::{MemoryBackend, MemorySmt, RecordingBackend, Smt};
+///
+///     let mut tree = MemorySmt::default();
+///     let root = tree.insert(MemorySmt::empty_tree_root(), "foo", b"bar".to_vec())?;
+///
+///     // dry-run on the host, recording the touched nodes
+///     let backend = RecordingBackend::new(tree.into_backend());
+///     let mut host = Smt::<_, Blake3Context>::from(backend);
+///     let expected = host.insert(root, "foo", b"baz".to_vec())?;
+///     let witness = host.backend().witness();
+///
+///     // replay in the guest, out of the witness alone
+///     let mut guest = MemorySmt::from_witness(&witness)?;
+///
+///     assert_eq!(expected, guest.insert(root, "foo", b"baz".to_vec())?);
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecordingBackend<B, H = Hash> {
+    inner: B,
+    recording: RefCell<Recording<H>>,
+}
+
+impl<B: Default, H> Default for RecordingBackend<B, H> {
+    fn default() -> Self {
+        Self::new(B::default())
+    }
+}
+
+impl<B, H> RecordingBackend<B, H> {
+    /// Wraps the provided backend with an empty recording.
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            recording: RefCell::new(Recording::default()),
+        }
+    }
+}
+
+impl<B, H: Digest> RecordingBackend<B, H> {
+    /// Returns the inner backend.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Returns the witness captured so far.
+    pub fn witness(&self) -> SmtWitness<H> {
+        let r = self.recording.borrow();
+
+        let mut children: Vec<_> = r.children.iter().map(|(p, c)| (*p, *c)).collect();
+        let mut node_keys: Vec<_> = r.node_keys.iter().map(|(n, k)| (*n, *k)).collect();
+        let mut key_data: Vec<_> = r.key_data.iter().map(|(k, d)| (*k, d.clone())).collect();
+        let mut childless: Vec<_> = r.childless.iter().copied().collect();
+        let mut keyless: Vec<_> = r.keyless.iter().copied().collect();
+        let mut dataless: Vec<_> = r.dataless.iter().copied().collect();
+
+        children.sort_unstable();
+        node_keys.sort_unstable();
+        key_data.sort_unstable();
+        childless.sort_unstable();
+        keyless.sort_unstable();
+        dataless.sort_unstable();
+
+        SmtWitness {
+            children: children.into_iter().map(|(_, c)| c).collect(),
+            node_keys,
+            key_data,
+            childless,
+            keyless,
+            dataless,
+        }
+    }
+
+    /// Discards the recording, starting a new dry-run.
+    pub fn reset(&self) {
+        self.recording.take();
+    }
+
+    /// Unwraps the inner backend and the captured witness.
+    pub fn into_parts(self) -> (B, SmtWitness<H>) {
+        let witness = self.witness();
+
+        (self.inner, witness)
+    }
+}
+
+impl<B, H> RecordingBackend<B, H>
+where
+    B: TreeBackend<H>,
+    H: Digest,
+{
+    /// Records the leaf key of the node, along with its data, since the replay checks every leaf
+    /// node against the hash of its data.
+    fn record_node_key(&self, node: &H, key: Option<H>) -> anyhow::Result<()> {
+        let mut recording = self.recording.borrow_mut();
+
+        if node == &H::zero() || recording.node_keys_written.contains(node) {
+            return Ok(());
+        }
+
+        if let Some(k) = &key {
+            if !recording.key_data.contains_key(k) {
+                let data = self.inner.get_key_data(k)?;
+
+                recording.key_data(k, data.as_ref());
+            }
+        }
+
+        recording.node_key(node, key);
+
+        Ok(())
+    }
+}
+
+impl<B, H> TreeBackend<H> for RecordingBackend<B, H>
+where
+    B: TreeBackend<H>,
+    H: Digest,
+{
+    fn insert_children(&mut self, parent: &H, children: &SmtChildren<H>) -> anyhow::Result<bool> {
+        self.recording.get_mut().children_written.insert(*parent);
+        self.inner.insert_children(parent, children)
+    }
+
+    fn get_children(&self, parent: &H) -> anyhow::Result<Option<SmtChildren<H>>> {
+        let children = self.inner.get_children(parent)?;
+
+        self.recording.borrow_mut().children(parent, children);
+
+        Ok(children)
+    }
+
+    fn remove_children(&mut self, parent: &H) -> anyhow::Result<Option<SmtChildren<H>>> {
+        let children = self.inner.remove_children(parent)?;
+        let recording = self.recording.get_mut();
+
+        recording.children(parent, children);
+        recording.children_written.insert(*parent);
+
+        Ok(children)
+    }
+
+    fn insert_node_key(&mut self, node: &H, leaf: &H) -> anyhow::Result<bool> {
+        self.recording.get_mut().node_keys_written.insert(*node);
+        self.inner.insert_node_key(node, leaf)
+    }
+
+    fn has_node_key(&self, node: &H) -> anyhow::Result<bool> {
+        // the key is recorded, so the replay can also resolve it
+        self.get_node_key(node).map(|k| k.is_some())
+    }
+
+    fn get_node_key(&self, node: &H) -> anyhow::Result<Option<H>> {
+        let key = self.inner.get_node_key(node)?;
+
+        self.record_node_key(node, key)?;
+
+        Ok(key)
+    }
+
+    fn remove_node_key(&mut self, node: &H) -> anyhow::Result<Option<H>> {
+        let key = self.inner.remove_node_key(node)?;
+
+        self.record_node_key(node, key)?;
+        self.recording.get_mut().node_keys_written.insert(*node);
+
+        Ok(key)
+    }
+
+    fn insert_key_data(&mut self, key: &H, data: Vec<u8>) -> anyhow::Result<bool> {
+        self.recording.get_mut().key_data_written.insert(*key);
+        self.inner.insert_key_data(key, data)
+    }
+
+    fn get_key_data(&self, key: &H) -> anyhow::Result<Option<Vec<u8>>> {
+        let data = self.inner.get_key_data(key)?;
+
+        self.recording.borrow_mut().key_data(key, data.as_ref());
+
+        Ok(data)
+    }
+
+    fn remove_key_data(&mut self, key: &H) -> anyhow::Result<Option<Vec<u8>>> {
+        let data = self.inner.remove_key_data(key)?;
+        let recording = self.recording.get_mut();
+
+        recording.key_data(key, data.as_ref());
+        recording.key_data_written.insert(*key);
+
+        Ok(data)
+    }
+
+    fn insert_node_refs(&mut self, node: &H, refs: u64) -> anyhow::Result<bool> {
+        self.inner.insert_node_refs(node, refs)
+    }
+
+    fn get_node_refs(&self, node: &H) -> anyhow::Result<Option<u64>> {
+        self.inner.get_node_refs(node)
+    }
+
+    fn remove_node_refs(&mut self, node: &H) -> anyhow::Result<Option<u64>> {
+        self.inner.remove_node_refs(node)
+    }
+}
+
+impl<B, C> Smt<B, C>
+where
+    B: TreeBackend<HashOf<C>> + Default,
+    C: ExecutionContext,
+{
+    /// Creates a tree out of a witness captured by a [RecordingBackend], so the recorded
+    /// operations can be replayed.
+    pub fn from_witness(witness: &SmtWitness<HashOf<C>>) -> anyhow::Result<Self> {
+        let mut backend = B::default();
+
+        witness.load::<B, C>(&mut backend)?;
+
+        Ok(Self::from(backend))
+    }
+}
+
+/// A [TreeBackend] built solely from a [SmtWitness], for tree operations inside zkVM guests.
+///
+/// Unlike a backend merely loaded with the witness, any read of a relationship that is neither
+/// part of the witness nor written afterwards fails, instead of being silently treated as absent.
+/// This way, a guest program can run lookups and insertions with [Smt], and commit the resulting
+/// root, while an incomplete witness aborts the execution.
+///
+/// Node reference counts are local to the instance; they start at zero for witness nodes.
+///
+/// # Example
+///
+/// ```rust
+/// #[cfg(feature = "memory")]
+/// fn run() -> anyhow::Result<()> {
+///     use valence_coprocessor_core::Blake3Context;
+///     use valence_smt::{MemorySmt, RecordingBackend, Smt, WitnessBackend};
+///
+///     let mut tree = MemorySmt::default();
+///     let root = tree.insert(MemorySmt::empty_tree_root(), "foo", b"bar".to_vec())?;
+///
+///     // dry-run on the host, recording the touched nodes
+///     let backend = RecordingBackend::new(tree.into_backend());
+///     let mut host = Smt::<_, Blake3Context>::from(backend);
+///     let expected = host.insert(root, "foo", b"baz".to_vec())?;
+///     let witness = borsh::to_vec(&host.backend().witness())?;
+///
+///     // the guest reads the serialized witness
+///     let backend = WitnessBackend::try_from_slice::<Blake3Context>(&witness)?;
+///     let mut guest = Smt::<_, Blake3Context>::from(backend);
+///
+///     assert_eq!(expected, guest.insert(root, "foo", b"baz".to_vec())?);
+///
+///     // the data of another tree is outside the witness
+///     assert!(guest.get_opening("foo", root, b"qux").is_err());
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WitnessBackend<H = Hash> {
+    children: HashMap<H, Option<SmtChildren<H>>>,
+    node_keys: HashMap<H, Option<H>>,
+    key_data: HashMap<H, Option<Vec<u8>>>,
+    refs: HashMap<H, u64>,
+}
+
+impl<H> Default for WitnessBackend<H> {
+    fn default() -> Self {
+        Self {
+            children: HashMap::new(),
+            node_keys: HashMap::new(),
+            key_data: HashMap::new(),
+            refs: HashMap::new(),
+        }
+    }
+}
+
+impl<H: Digest> WitnessBackend<H> {
+    /// Creates a backend out of the witness, recomputing the parent nodes with the hasher of the
+    /// execution context.
+    pub fn new<C>(witness: &SmtWitness<H>) -> anyhow::Result<Self>
+    where
+        C: ExecutionContext,
+        C::Hasher: Hasher<Output = H>,
+    {
+        let mut backend = Self::default();
+
+        witness.load::<Self, C>(&mut backend)?;
+
+        for node in &witness.childless {
+            backend.children.entry(*node).or_insert(None);
+        }
+
+        for node in &witness.keyless {
+            backend.node_keys.entry(*node).or_insert(None);
+        }
+
+        for key in &witness.dataless {
+            backend.key_data.entry(*key).or_insert(None);
+        }
+
+        Ok(backend)
+    }
+
+    /// Creates a backend out of a borsh serialized [SmtWitness].
+    pub fn try_from_slice<C>(bytes: &[u8]) -> anyhow::Result<Self>
+    where
+        C: ExecutionContext,
+        C::Hasher: Hasher<Output = H>,
+    {
+        let witness = borsh::from_slice(bytes)
+            .map_err(|e| anyhow::anyhow!("failed to deserialize the witness: {e}"))?;
+
+        Self::new::<C>(&witness)
+    }
+}
+
+impl<H: Digest> TreeBackend<H> for WitnessBackend<H> {
+    fn insert_children(&mut self, parent: &H, children: &SmtChildren<H>) -> anyhow::Result<bool> {
+        Ok(self
+            .children
+            .insert(*parent, Some(*children))
+            .flatten()
+            .is_some())
+    }
+
+    fn get_children(&self, parent: &H) -> anyhow::Result<Option<SmtChildren<H>>> {
+        if parent == &H::zero() {
+            return Ok(None);
+        }
+
+        match self.children.get(parent) {
+            Some(c) => Ok(*c),
+            None => anyhow::bail!("the children of node {parent:x?} are outside the witness"),
+        }
+    }
+
+    fn remove_children(&mut self, parent: &H) -> anyhow::Result<Option<SmtChildren<H>>> {
+        let children = self.get_children(parent)?;
+
+        self.children.insert(*parent, None);
+
+        Ok(children)
+    }
+
+    fn insert_node_key(&mut self, node: &H, leaf: &H) -> anyhow::Result<bool> {
+        Ok(self
+            .node_keys
+            .insert(*node, Some(*leaf))
+            .flatten()
+            .is_some())
+    }
+
+    fn has_node_key(&self, node: &H) -> anyhow::Result<bool> {
+        Ok(self.get_node_key(node)?.is_some())
+    }
+
+    fn get_node_key(&self, node: &H) -> anyhow::Result<Option<H>> {
+        if node == &H::zero() {
+            return Ok(None);
+        }
+
+        match self.node_keys.get(node) {
+            Some(k) => Ok(*k),
+            None => anyhow::bail!("the leaf key of node {node:x?} is outside the witness"),
+        }
+    }
+
+    fn remove_node_key(&mut self, node: &H) -> anyhow::Result<Option<H>> {
+        let key = self.get_node_key(node)?;
+
+        self.node_keys.insert(*node, None);
+
+        Ok(key)
+    }
+
+    fn insert_key_data(&mut self, key: &H, data: Vec<u8>) -> anyhow::Result<bool> {
+        Ok(self.key_data.insert(*key, Some(data)).flatten().is_some())
+    }
+
+    fn get_key_data(&self, key: &H) -> anyhow::Result<Option<Vec<u8>>> {
+        match self.key_data.get(key) {
+            Some(d) => Ok(d.clone()),
+            None => anyhow::bail!("the data of leaf key {key:x?} is outside the witness"),
+        }
+    }
+
+    fn remove_key_data(&mut self, key: &H) -> anyhow::Result<Option<Vec<u8>>> {
+        let data = self.get_key_data(key)?;
+
+        self.key_data.insert(*key, None);
+
+        Ok(data)
+    }
+
+    fn insert_node_refs(&mut self, node: &H, refs: u64) -> anyhow::Result<bool> {
+        Ok(self.refs.insert(*node, refs).is_some())
+    }
+
+    fn get_node_refs(&self, node: &H) -> anyhow::Result<Option<u64>> {
+        Ok(self.refs.get(node).copied())
+    }
+
+    fn remove_node_refs(&mut self, node: &H) -> anyhow::Result<Option<u64>> {
+        Ok(self.refs.remove(node))
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use alloc::vec;
+    use valence_coprocessor_core::Blake3Context;
+
+    use crate::{MemoryBackend, MemorySmt};
+
+    use super::*;
+
+    type RecordingSmt = Smt<RecordingBackend<MemoryBackend>, Blake3Context>;
+
+    fn sample(count: u8) -> anyhow::Result<(MemorySmt, Hash)> {
+        let mut tree = MemorySmt::default();
+        let mut root = MemorySmt::empty_tree_root();
+
+        for i in 0..count {
+            root = tree.insert(root, "poem", vec![0x00, 0x00, i])?;
+        }
+
+        Ok((tree, root))
+    }
+
+    #[test]
+    fn replay_openings() -> anyhow::Result<()> {
+        let (tree, root) = sample(50)?;
+        let tree = RecordingSmt::from(RecordingBackend::new(tree.into_backend()));
+
+        let data = [[0x00, 0x00, 3], [0x00, 0x00, 41]];
+        let proofs = [
+            tree.get_opening("poem", root, &data[0])?.unwrap(),
+            tree.get_opening("poem", root, &data[1])?.unwrap(),
+        ];
+
+        let witness = tree.backend().witness();
+
+        assert!(witness.key_data.len() == 2);
+        assert!(witness.children.len() <= proofs[0].opening.len() + proofs[1].opening.len());
+
+        let replay = MemorySmt::from_witness(&witness)?;
+
+        for (d, p) in data.iter().zip(proofs.iter()) {
+            assert_eq!(p, &replay.get_opening("poem", root, d)?.unwrap());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn replay_inserts() -> anyhow::Result<()> {
+        let (tree, root) = sample(50)?;
+        let mut tree = RecordingSmt::from(RecordingBackend::new(tree.into_backend()));
+
+        let mut roots = vec![root];
+
+        for i in 0..10u8 {
+            let root = tree.insert(*roots.last().unwrap(), "poem", vec![0x01, 0x00, i])?;
+
+            roots.push(root);
+        }
+
+        let opening = tree
+            .get_opening("poem", roots[10], &[0x00, 0x00, 7])?
+            .unwrap();
+        let witness = tree.backend().witness();
+        let witness: SmtWitness = borsh::from_slice(&borsh::to_vec(&witness)?)?;
+
+        let mut replay = MemorySmt::from_witness(&witness)?;
+
+        for i in 0..10u8 {
+            let root = replay.insert(roots[i as usize], "poem", vec![0x01, 0x00, i])?;
+
+            assert_eq!(roots[i as usize + 1], root);
+        }
+
+        assert_eq!(
+            opening,
+            replay
+                .get_opening("poem", roots[10], &[0x00, 0x00, 7])?
+                .unwrap()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn reset_discards_recording() -> anyhow::Result<()> {
+        let (tree, root) = sample(10)?;
+        let tree = RecordingSmt::from(RecordingBackend::new(tree.into_backend()));
+
+        tree.get_opening("poem", root, &[0x00, 0x00, 3])?.unwrap();
+
+        assert!(!tree.backend().witness().is_empty());
+
+        tree.backend().reset();
+
+        assert!(tree.backend().witness().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn witness_backend_replays_inserts() -> anyhow::Result<()> {
+        let (tree, root) = sample(50)?;
+        let mut tree = RecordingSmt::from(RecordingBackend::new(tree.into_backend()));
+
+        tree.get_opening("poem", root, &[0x00, 0x00, 7])?.unwrap();
+        tree.get_opening("poem", root, &[0x02, 0x00, 0x00])?;
+
+        let updated = tree.insert(root, "poem", vec![0x01, 0x00, 0x00])?;
+        let updated = tree.insert(updated, "poem", vec![0x01, 0x00, 0x01])?;
+
+        let witness = borsh::to_vec(&tree.backend().witness())?;
+        let backend = WitnessBackend::try_from_slice::<Blake3Context>(&witness)?;
+        let mut replay = Smt::<_, Blake3Context>::from(backend);
+
+        assert!(replay
+            .get_opening("poem", root, &[0x00, 0x00, 7])?
+            .is_some());
+        assert!(replay
+            .get_opening("poem", root, &[0x02, 0x00, 0x00])?
+            .is_none());
+
+        let root = replay.insert(root, "poem", vec![0x01, 0x00, 0x00])?;
+        let root = replay.insert(root, "poem", vec![0x01, 0x00, 0x01])?;
+
+        assert_eq!(updated, root);
+
+        Ok(())
+    }
+
+    #[test]
+    fn witness_backend_rejects_access_outside_witness() -> anyhow::Result<()> {
+        let (tree, root) = sample(50)?;
+        let tree = RecordingSmt::from(RecordingBackend::new(tree.into_backend()));
+
+        tree.get_opening("poem", root, &[0x00, 0x00, 7])?.unwrap();
+
+        let witness = tree.backend().witness();
+        let backend = WitnessBackend::new::<Blake3Context>(&witness)?;
+        let mut replay = Smt::<_, Blake3Context>::from(backend);
+
+        // unrecorded leaf data
+        assert!(replay.get_opening("poem", root, &[0x00, 0x00, 8]).is_err());
+
+        // unrecorded path
+        assert!(replay.insert(root, "poem", vec![0x01, 0x00, 0x00]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_rejects_forged_leaf() -> anyhow::Result<()> {
+        let witness = SmtWitness {
+            node_keys: vec![([0xff; 32], [0x01; 32])],
+            key_data: vec![([0x01; 32], b"foo".to_vec())],
+            ..Default::default()
+        };
+
+        assert!(MemorySmt::from_witness(&witness).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_rejects_contradicting_claims() -> anyhow::Result<()> {
+        let (tree, root) = sample(50)?;
+        let mut tree = RecordingSmt::from(RecordingBackend::new(tree.into_backend()));
+
+        for i in 0..10u8 {
+            tree.insert(root, "poem", vec![0x01, 0x00, i])?;
+        }
+
+        let witness = tree.backend().witness();
+        let parent = witness.children[0].parent::<Blake3Context>();
+        let (leaf, key) = witness.node_keys[0];
+
+        assert!(WitnessBackend::new::<Blake3Context>(&witness).is_ok());
+
+        // a leaf without its data
+        let mut forged = witness.clone();
+        forged.key_data.retain(|(k, _)| k != &key);
+        assert!(WitnessBackend::new::<Blake3Context>(&forged).is_err());
+
+        // a parent claimed to have no children
+        let mut forged = witness.clone();
+        forged.childless.push(parent);
+        assert!(WitnessBackend::new::<Blake3Context>(&forged).is_err());
+
+        // a leaf claimed to have no key
+        let mut forged = witness.clone();
+        forged.keyless.push(leaf);
+        assert!(WitnessBackend::new::<Blake3Context>(&forged).is_err());
+
+        // a leaf key claimed to have no data
+        let mut forged = witness;
+        forged.dataless.push(key);
+        assert!(WitnessBackend::new::<Blake3Context>(&forged).is_err());
+
+        Ok(())
+    }
+}